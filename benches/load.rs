@@ -0,0 +1,52 @@
+//! Times loading a large generated CSV, run with `cargo bench`, and with
+//! `cargo bench --features rayon` to compare parsing the rows in parallel
+
+use std::time::{Duration, Instant};
+
+const ROWS: usize = 200_000;
+const RUNS: u32 = 5;
+
+/// A quote for every minute, over and over, with a broken row now and then
+fn generate(rows: usize) -> Vec<u8> {
+    let mut csv = String::with_capacity(rows * 120);
+    for i in 0..rows {
+        let (hh, mm) = (i / 60 % 24, i % 60);
+        if i % 1000 == 999 {
+            csv.push_str("not a row\n");
+            continue;
+        }
+        csv.push_str(&format!(
+            "{:02}:{:02}|the minute|At the minute {} the clock struck, once and then again.|Book {} |Author {} \n",
+            hh,
+            mm,
+            i,
+            i % 500,
+            i % 300
+        ));
+    }
+    csv.into_bytes()
+}
+
+fn main() {
+    let csv = generate(ROWS);
+    let mut fastest = Duration::MAX;
+    for _ in 0..RUNS {
+        let started = Instant::now();
+        let (quotes, skipped) = literal::load_quotes_counted(&csv[..]).expect("valid csv");
+        fastest = fastest.min(started.elapsed());
+        assert_eq!(quotes.len() + skipped, ROWS);
+    }
+    let mode = if cfg!(feature = "rayon") {
+        "in parallel"
+    } else {
+        "in order"
+    };
+    println!(
+        "loaded {} rows ({} KiB) {} in {:?}, the fastest of {} runs",
+        ROWS,
+        csv.len() / 1024,
+        mode,
+        fastest,
+        RUNS
+    );
+}
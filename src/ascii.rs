@@ -0,0 +1,133 @@
+use crate::Quote;
+
+impl Quote {
+    /// Returns a copy of the quote with its text, context, author and source in
+    /// plain ASCII, see `to_ascii`
+    pub fn to_ascii(&self) -> Self {
+        self.map_text(to_ascii)
+    }
+}
+
+/// Replaces curly quotes, dashes, accented letters and the like with their
+/// closest plain ASCII, and anything else that isn't ASCII with `?`
+pub fn to_ascii(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if ch.is_ascii() {
+            out.push(ch);
+        } else {
+            out.push_str(transliterate(ch));
+        }
+    }
+    out
+}
+
+fn transliterate(ch: char) -> &'static str {
+    match ch {
+        '‘' | '’' | '‚' | '‛' | '′' | '´' => "'",
+        '“' | '”' | '„' | '‟' | '″' => "\"",
+        '‐' | '‑' | '‒' | '–' | '−' => "-",
+        '—' | '―' => "--",
+        '…' => "...",
+        '«' => "<<",
+        '»' => ">>",
+        '•' | '·' => "*",
+        '°' => " degrees",
+        '£' => "GBP",
+        '€' => "EUR",
+        '©' => "(c)",
+        '\u{a0}' | '\u{2009}' | '\u{202f}' => " ",
+        '─' | '━' | '═' => "-",
+        '│' | '┃' | '║' => "|",
+        '┌' | '┐' | '└' | '┘' | '╭' | '╮' | '╯' | '╰' | '╔' | '╗' | '╚' | '╝' | '├' | '┤' | '┬'
+        | '┴' | '┼' => "+",
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'ç' | 'ć' | 'č' => "c",
+        'Ç' | 'Ć' | 'Č' => "C",
+        'ď' | 'đ' | 'ð' => "d",
+        'Ď' | 'Đ' | 'Ð' => "D",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'ğ' => "g",
+        'Ğ' => "G",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ī' | 'Į' | 'İ' => "I",
+        'ł' => "l",
+        'Ł' => "L",
+        'ñ' | 'ń' | 'ň' => "n",
+        'Ñ' | 'Ń' | 'Ň' => "N",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' | 'Ø' | 'Ō' | 'Ő' => "O",
+        'ř' => "r",
+        'Ř' => "R",
+        'ś' | 'š' | 'ş' => "s",
+        'Ś' | 'Š' | 'Ş' => "S",
+        'ť' | 'ţ' => "t",
+        'Ť' | 'Ţ' => "T",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u",
+        'Ù' | 'Ú' | 'Û' | 'Ü' | 'Ū' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'ý' | 'ÿ' => "y",
+        'Ý' | 'Ÿ' => "Y",
+        'ź' | 'ż' | 'ž' => "z",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'æ' => "ae",
+        'Æ' => "AE",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'ß' => "ss",
+        'þ' => "th",
+        'Þ' => "Th",
+        _ => "?",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColorSet, Layout};
+    use termcolor::{Buffer, WriteColor};
+
+    #[test]
+    fn punctuation_and_accents_have_plain_forms() {
+        assert_eq!(
+            to_ascii("“Déjà vu” – it’s 5° at the café… — «Zoë»"),
+            "\"Deja vu\" - it's 5 degrees at the cafe... -- <<Zoe>>"
+        );
+        assert_eq!(to_ascii("Straße Æsop Œuvre"), "Strasse AEsop OEuvre");
+    }
+
+    #[test]
+    fn anything_else_is_a_question_mark() {
+        assert_eq!(to_ascii("ten ☕ past 十"), "ten ? past ?");
+        assert_eq!(to_ascii("plain"), "plain");
+    }
+
+    #[test]
+    fn the_highlight_stays_on_the_context() {
+        let quote = Quote::new(
+            "10:00",
+            "ten o’clock",
+            "Émile said “it’s ten o’clock”.",
+            "L’Œuvre",
+            "Émile Zola",
+        )
+        .unwrap()
+        .to_ascii();
+        assert_eq!(quote.quote(), "Emile said \"it's ten o'clock\".");
+        assert_eq!(quote.author(), "Emile Zola");
+        let range = quote.highlight_range().unwrap();
+        assert_eq!(&quote.quote()[range], "ten o'clock");
+
+        let colors = ColorSet::standard();
+        let mut highlight = Buffer::ansi();
+        highlight.set_color(&colors.highlight).unwrap();
+        let highlight = String::from_utf8(highlight.into_inner()).unwrap();
+        let mut buffer = Buffer::ansi();
+        quote
+            .format_no_wrap(&mut buffer, &colors, &Layout::default())
+            .unwrap();
+        let out = String::from_utf8(buffer.into_inner()).unwrap();
+        assert!(out.contains(&format!("\"it's {}ten o'clock", highlight)));
+    }
+}
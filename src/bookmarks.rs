@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use crate::{Error, Quote};
+
+/// Favorite quotes, kept in a CSV in the same format as the quotes
+pub struct Bookmarks {
+    path: PathBuf,
+}
+
+impl Bookmarks {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// The bookmarks in the platform's data directory, if there is one
+    pub fn user() -> Option<Self> {
+        dirs::data_dir()
+            .map(|dir| Self::new(dir.join(env!("CARGO_PKG_NAME")).join("bookmarks.csv")))
+    }
+
+    /// Loads the bookmarked quotes, there are none if the file doesn't exist yet
+    pub fn load(&self) -> Result<Vec<Quote>, Error> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+        crate::load_quotes_from_path_as(&self.path, crate::FileFormat::Csv)
+    }
+
+    /// Adds the quote, returning `false` if it was already bookmarked
+    pub fn add(&self, quote: &Quote) -> Result<bool, Error> {
+        if self.load()?.contains(quote) {
+            return Ok(false);
+        }
+
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        let mut writer = crate::csv_writer_builder().from_writer(file);
+        writer.serialize(quote)?;
+        writer.flush()?;
+        Ok(true)
+    }
+}
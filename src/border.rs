@@ -0,0 +1,187 @@
+use unicode_width::UnicodeWidthStr;
+
+/// A box drawn around a rendered quote
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Border {
+    None,
+    Single,
+    Rounded,
+    Double,
+}
+
+impl Border {
+    /// The top left, top right, bottom left and bottom right corners, then the
+    /// horizontal and vertical lines
+    fn pieces(self) -> Option<[char; 6]> {
+        match self {
+            Border::None => None,
+            Border::Single => Some(['┌', '┐', '└', '┘', '─', '│']),
+            Border::Rounded => Some(['╭', '╮', '╰', '╯', '─', '│']),
+            Border::Double => Some(['╔', '╗', '╚', '╝', '═', '║']),
+        }
+    }
+
+    /// Draws the border around the rendered lines, which can contain color
+    /// escapes.
+    ///
+    /// The box is at least `width` columns inside, but grows to fit the
+    /// longest line. Blank lines before and after the text are dropped
+    pub fn draw(self, rendered: &[u8], width: usize) -> Vec<u8> {
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] =
+            match self.pieces() {
+                Some(pieces) => pieces,
+                None => return rendered.to_vec(),
+            };
+
+        let rendered = String::from_utf8_lossy(rendered);
+        let lines = rendered
+            .lines()
+            .map(|line| (line, strip_escapes(line)))
+            .skip_while(|(_, text)| text.trim().is_empty())
+            .collect::<Vec<_>>();
+        let end = lines
+            .iter()
+            .rposition(|(_, text)| !text.trim().is_empty())
+            .map_or(0, |i| i + 1);
+        let lines = &lines[..end];
+
+        let inner = lines
+            .iter()
+            .map(|(_, text)| text.width())
+            .max()
+            .unwrap_or(0)
+            .max(width);
+        let rule = horizontal.to_string().repeat(inner + 2);
+
+        let mut out = format!("{}{}{}\n", top_left, rule, top_right);
+        for (line, text) in lines {
+            // the border itself shouldn't pick up the colors of the line
+            let reset = if line.len() != text.len() {
+                "\x1b[0m"
+            } else {
+                ""
+            };
+            let padding = " ".repeat(inner - text.width());
+            out.push_str(&format!(
+                "{} {}{}{} {}\n",
+                vertical, line, reset, padding, vertical
+            ));
+        }
+        out.push_str(&format!("{}{}{}\n", bottom_left, rule, bottom_right));
+        out.into_bytes()
+    }
+}
+
+impl std::str::FromStr for Border {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Border::None),
+            "single" => Ok(Border::Single),
+            "rounded" => Ok(Border::Rounded),
+            "double" => Ok(Border::Double),
+            _ => Err(String::from(
+                "Unknown border, available: none, single, rounded, double",
+            )),
+        }
+    }
+}
+
+/// Removes the terminal escape sequences, leaving the text that's shown
+fn strip_escapes(line: &str) -> String {
+    let mut text = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            text.push(ch);
+            continue;
+        }
+        // a control sequence is `ESC [`, some parameters then a final letter
+        if chars.next() == Some('[') {
+            for ch in chars.by_ref() {
+                if ('@'..='~').contains(&ch) {
+                    break;
+                }
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(border: Border, rendered: &str, width: usize) -> Vec<String> {
+        let drawn = String::from_utf8(border.draw(rendered.as_bytes(), width)).unwrap();
+        drawn.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn the_corners_are_the_borders() {
+        for &(border, corners) in &[
+            (Border::Single, "┌┐└┘"),
+            (Border::Rounded, "╭╮╰╯"),
+            (Border::Double, "╔╗╚╝"),
+        ] {
+            let lines = draw(border, "\n  It was noon.\n\n", 10);
+            let (top, bottom) = (&lines[0], lines.last().unwrap());
+            let found = [
+                top.chars().next(),
+                top.chars().last(),
+                bottom.chars().next(),
+                bottom.chars().last(),
+            ];
+            assert!(found.iter().copied().eq(corners.chars().map(Some)));
+        }
+        assert_eq!(Border::None.draw(b"\n  noon\n", 10), b"\n  noon\n");
+    }
+
+    #[test]
+    fn each_line_is_padded_to_the_same_width() {
+        let rendered = "\n  It was noon, said\n    the clock.\n\n        Author – Source\n\n";
+        let lines = draw(Border::Single, rendered, 10);
+        // the box grows past the width to fit the longest line
+        assert_eq!(
+            lines,
+            [
+                "┌─────────────────────────┐",
+                "│   It was noon, said     │",
+                "│     the clock.          │",
+                "│                         │",
+                "│         Author – Source │",
+                "└─────────────────────────┘",
+            ]
+        );
+
+        let lines = draw(Border::Double, "  noon\n", 8);
+        assert_eq!(lines, ["╔══════════╗", "║   noon   ║", "╚══════════╝"]);
+    }
+
+    #[test]
+    fn the_colors_are_reset_before_the_border() {
+        let rendered = "\x1b[0m\x1b[37m  It was \x1b[0m\x1b[31mnoon\x1b[0m\n";
+        let lines = draw(Border::Single, rendered, 16);
+        assert_eq!(lines[0], "┌──────────────────┐");
+        assert_eq!(
+            lines[1],
+            "│ \x1b[0m\x1b[37m  It was \x1b[0m\x1b[31mnoon\x1b[0m\x1b[0m    │"
+        );
+        assert_eq!(strip_escapes(&lines[1]).width(), lines[0].width());
+    }
+
+    #[test]
+    fn wide_characters_line_up_with_the_border() {
+        let lines = draw(Border::Single, "  正午です\n  noon\n", 4);
+        assert_eq!(
+            lines,
+            [
+                "┌────────────┐",
+                "│   正午です │",
+                "│   noon     │",
+                "└────────────┘"
+            ]
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, FileFormat, Quote};
+
+/// An on-disk cache of parsed quote files, keyed by their path, modification
+/// time and the format they were parsed as
+pub struct QuoteCache {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    path: PathBuf,
+    modified: (u64, u32),
+    format: FileFormat,
+    quotes: Vec<Quote>,
+    skipped: usize,
+}
+
+impl QuoteCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// A cache in the platform's cache directory, if there is one
+    pub fn user() -> Option<Self> {
+        dirs::cache_dir().map(|dir| Self::new(dir.join(env!("CARGO_PKG_NAME"))))
+    }
+
+    /// Loads the quotes for the file, and how many of its rows were skipped,
+    /// if they were parsed as the format and cached since it was last modified
+    pub fn load(&self, path: &Path, format: FileFormat) -> Option<(Vec<Quote>, usize)> {
+        let path = path.canonicalize().ok()?;
+        let modified = modified(&path)?;
+
+        // reading from a slice bounds the lengths in a corrupt entry, which is treated as a miss
+        let data = std::fs::read(self.entry_path(&path)).ok()?;
+        let entry: Entry = bincode::deserialize(&data).ok()?;
+        if entry.path != path || entry.modified != modified || entry.format != format {
+            return None;
+        }
+        Some((entry.quotes, entry.skipped))
+    }
+
+    /// Caches the quotes parsed from the file as the format, and how many of
+    /// its rows were skipped
+    pub fn store(
+        &self,
+        path: &Path,
+        format: FileFormat,
+        quotes: &[Quote],
+        skipped: usize,
+    ) -> Result<(), Error> {
+        let path = path.canonicalize()?;
+        let modified =
+            modified(&path).ok_or_else(|| std::io::Error::other("no modification time"))?;
+
+        std::fs::create_dir_all(&self.dir)?;
+        let entry = Entry {
+            path: path.clone(),
+            modified,
+            format,
+            quotes: quotes.to_vec(),
+            skipped,
+        };
+
+        let file = std::fs::File::create(self.entry_path(&path))?;
+        bincode::serialize_into(std::io::BufWriter::new(file), &entry)
+            .map_err(|err| std::io::Error::other(err).into())
+    }
+
+    fn entry_path(&self, path: &Path) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        self.dir.join(format!("{:016x}.bin", hasher.finish()))
+    }
+}
+
+fn modified(path: &Path) -> Option<(u64, u32)> {
+    let time = std::fs::metadata(path).ok()?.modified().ok()?;
+    let since = time.duration_since(UNIX_EPOCH).ok()?;
+    Some((since.as_secs(), since.subsec_nanos()))
+}
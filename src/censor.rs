@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+use crate::Quote;
+
+// a short list of the strongest words, so --censor works without a list of its own
+const BUILTIN_WORDS: &str = include_str!("../etc/censored_words.txt");
+
+/// Words that shouldn't be shown, matched on word boundaries and ignoring case
+pub struct Censor {
+    words: HashSet<String>,
+}
+
+impl Censor {
+    /// Reads the words from a list with one word per line, ignoring blank
+    /// lines and comments starting with `#`
+    pub fn new(list: &str) -> Self {
+        let words = list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_lowercase)
+            .collect();
+        Self { words }
+    }
+
+    /// The words embedded in the binary
+    pub fn builtin() -> Self {
+        Self::new(BUILTIN_WORDS)
+    }
+
+    /// Whether the quote, its author and its source have none of the words
+    pub fn is_clean(&self, quote: &Quote) -> bool {
+        let fields = [quote.quote(), quote.author(), quote.source()];
+        !fields
+            .iter()
+            .flat_map(|field| words(field))
+            .any(|(_, word)| self.is_censored(word))
+    }
+
+    /// Returns a copy of the quote with each of the words replaced by asterisks
+    pub fn mask(&self, quote: &Quote) -> Quote {
+        quote.map_text(|text| {
+            let mut masked = String::with_capacity(text.len());
+            let mut written = 0;
+            for (start, word) in words(text)
+                .into_iter()
+                .filter(|(_, word)| self.is_censored(word))
+            {
+                masked.push_str(&text[written..start]);
+                masked.extend(word.chars().map(|_| '*'));
+                written = start + word.len();
+            }
+            masked.push_str(&text[written..]);
+            masked
+        })
+    }
+
+    fn is_censored(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+}
+
+/// Each run of letters and digits in the text, with where it starts
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+    for (i, ch) in text.char_indices().chain(Some((text.len(), ' '))) {
+        match (start, ch.is_alphanumeric()) {
+            (None, true) => start = Some(i),
+            (Some(from), false) => {
+                words.push((from, &text[from..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(text: &str, source: &str, author: &str) -> Quote {
+        Quote::new("12:00", "noon", text, source, author).unwrap()
+    }
+
+    fn censor() -> Censor {
+        Censor::new("# mild ones, for the tests\n\n  Drat \nblast\n")
+    }
+
+    #[test]
+    fn words_are_matched_whole_and_ignoring_case() {
+        let censor = censor();
+        assert!(!censor.is_clean(&quote("DRAT, it was noon.", "Source", "Author")));
+        assert!(!censor.is_clean(&quote("It was noon.", "Blast Off", "Author")));
+        assert!(!censor.is_clean(&quote("It was noon.", "Source", "Ed Blast")));
+        // inside of another word doesn't count
+        assert!(censor.is_clean(&quote("The blaster went off at noon.", "Source", "Author")));
+        assert!(censor.is_clean(&quote("Dratted noon.", "Source", "Author")));
+        // and neither does the comment
+        assert!(censor.is_clean(&quote("It was mild at noon.", "Source", "Author")));
+    }
+
+    #[test]
+    fn masking_replaces_each_letter() {
+        let masked = censor().mask(&quote("Drat! Noon, blast it, noon.", "Blast", "Author"));
+        assert_eq!(masked.quote(), "****! Noon, ***** it, noon.");
+        assert_eq!(masked.source(), "*****");
+        assert_eq!(masked.author(), "Author");
+        assert_eq!(masked.context(), "noon");
+        assert_eq!(masked.time(), "12:00");
+    }
+
+    #[test]
+    fn the_builtin_list_has_words() {
+        assert!(!Censor::builtin().words.is_empty());
+    }
+}
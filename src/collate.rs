@@ -0,0 +1,119 @@
+use crate::to_ascii;
+
+/// How names are put in order for a locale, by folding them down to plain
+/// lowercase letters rather than with the locale's full rules
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Collation {
+    /// By code point, as the `C` and `POSIX` locales do
+    CodePoint,
+    /// Ignoring case and accents, except for the letters the locale has
+    /// after `z`, in their order
+    Folded(&'static [char]),
+}
+
+impl Collation {
+    /// What the name is sorted by
+    pub fn key(self, name: &str) -> String {
+        let after_z = match self {
+            Collation::CodePoint => return name.to_string(),
+            Collation::Folded(after_z) => after_z,
+        };
+        let mut key = String::with_capacity(name.len());
+        for ch in name.chars().flat_map(char::to_lowercase) {
+            match after_z.iter().position(|&letter| letter == ch) {
+                // these sort straight after `z`
+                Some(pos) => key.push(char::from(b'{' + pos as u8)),
+                None => key.push_str(&to_ascii(ch.encode_utf8(&mut [0; 4])).to_lowercase()),
+            }
+        }
+        key
+    }
+
+    /// Sorts names with how many quotes they have, most quotes first, and
+    /// then by name
+    pub fn sort(self, counts: &mut [(&str, usize)]) {
+        counts.sort_by_cached_key(|&(name, count)| {
+            (std::cmp::Reverse(count), self.key(name), name.to_string())
+        });
+    }
+}
+
+impl std::str::FromStr for Collation {
+    type Err = String;
+
+    /// A locale like `en_US.UTF-8`, `sv_SE` or `C`. Only the language matters
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let valid = |c: char| c.is_ascii_alphanumeric() || "_-.@".contains(c);
+        if s.is_empty() || !s.chars().all(valid) {
+            return Err(String::from("The locale must look like en_US.UTF-8, or C"));
+        }
+        let language = s.split(['_', '-', '.', '@']).next().unwrap_or_default();
+        Ok(match language.to_ascii_lowercase().as_str() {
+            "c" | "posix" => Collation::CodePoint,
+            "sv" | "fi" => Collation::Folded(&['å', 'ä', 'ö']),
+            "da" | "nb" | "nn" | "no" => Collation::Folded(&['æ', 'ø', 'å']),
+            _ => Collation::Folded(&[]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(locale: &str, names: &[&'static str]) -> Vec<&'static str> {
+        let mut counts = names.iter().map(|&name| (name, 1)).collect::<Vec<_>>();
+        locale.parse::<Collation>().unwrap().sort(&mut counts);
+        counts.into_iter().map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn accented_names_sort_beside_their_base() {
+        let names = ["Zola", "Éluard", "Eliot", "zweig"];
+        assert_eq!(
+            sorted("en_US.UTF-8", &names),
+            ["Eliot", "Éluard", "Zola", "zweig"]
+        );
+        assert_eq!(
+            sorted("fr", &["Œdipe", "Ovide", "Oates"]),
+            ["Oates", "Œdipe", "Ovide"]
+        );
+        // the C locale keeps the code point order
+        assert_eq!(sorted("C", &names), ["Eliot", "Zola", "zweig", "Éluard"]);
+        assert_eq!(sorted("POSIX", &names), sorted("C", &names));
+    }
+
+    #[test]
+    fn some_locales_put_letters_after_z() {
+        let names = ["Östergren", "Oates", "Zetterström", "Åsa"];
+        assert_eq!(
+            sorted("sv_SE", &names),
+            ["Oates", "Zetterström", "Åsa", "Östergren"]
+        );
+        assert_eq!(
+            sorted("en_GB", &names),
+            ["Åsa", "Oates", "Östergren", "Zetterström"]
+        );
+        assert_eq!(
+            sorted("da_DK", &["Ørsted", "Aakjær", "Zeuthen", "Æbelø"]),
+            ["Aakjær", "Zeuthen", "Æbelø", "Ørsted"]
+        );
+    }
+
+    #[test]
+    fn more_quotes_still_come_first() {
+        let collation = "en".parse::<Collation>().unwrap();
+        let mut counts = vec![("Zola", 1), ("Éluard", 1), ("Zweig", 3)];
+        collation.sort(&mut counts);
+        assert_eq!(counts, [("Zweig", 3), ("Éluard", 1), ("Zola", 1)]);
+    }
+
+    #[test]
+    fn parse_a_locale() {
+        assert_eq!("C".parse(), Ok(Collation::CodePoint));
+        assert_eq!("sv-FI".parse(), Ok(Collation::Folded(&['å', 'ä', 'ö'])));
+        assert_eq!("de_DE@euro".parse(), Ok(Collation::Folded(&[])));
+        assert!("".parse::<Collation>().is_err());
+        assert!("en US".parse::<Collation>().is_err());
+    }
+}
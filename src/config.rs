@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+use crate::Error;
+
+/// Defaults for the command line flags, so they don't have to be given every time.
+///
+/// A flag that's given always wins over the config
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub width: Option<usize>,
+    pub theme: Option<String>,
+    pub highlight: Option<String>,
+    pub inactive: Option<String>,
+    pub active: Option<String>,
+    /// `author=color` pairs separated by `;`, like `--author-colors`
+    pub author_colors: Option<String>,
+    pub direction: Option<String>,
+    pub timezone: Option<String>,
+    pub file: Option<String>,
+}
+
+impl Config {
+    /// Parses the config from TOML
+    pub fn from_toml(toml: &str) -> Result<Self, Error> {
+        toml::from_str(toml).map_err(Into::into)
+    }
+
+    /// Where the config is in the platform's config directory, if there is one
+    pub fn user_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(env!("CARGO_PKG_NAME")).join("config.toml"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_the_config() {
+        let config = Config::from_toml(
+            r#"
+            width = 40
+            theme = "mono"
+            direction = "forward"
+            author_colors = "Jane Austen=blue"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.width, Some(40));
+        assert_eq!(config.theme.as_deref(), Some("mono"));
+        assert_eq!(config.direction.as_deref(), Some("forward"));
+        assert_eq!(config.author_colors.as_deref(), Some("Jane Austen=blue"));
+        assert!(config.timezone.is_none() && config.file.is_none());
+
+        // everything is optional
+        assert!(Config::from_toml("").unwrap().width.is_none());
+    }
+
+    #[test]
+    fn unknown_keys_are_an_error() {
+        let err = Config::from_toml("witdh = 40").unwrap_err();
+        assert!(matches!(err, Error::Config(..)));
+    }
+}
@@ -0,0 +1,882 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use multimap::MultiMap;
+use rand::prelude::*;
+
+use crate::{Error, Quote, TimeKey};
+
+const MINUTES_PER_DAY: usize = 24 * 60;
+
+pub struct Database<'a> {
+    map: MultiMap<TimeKey, &'a Quote>,
+    quotes: Vec<&'a Quote>,
+    recent: Vec<&'a Quote>,
+    weight: Weight,
+    max_candidates: Option<usize>,
+    skipped: usize,
+    // positions in `quotes`, by trimmed author and source
+    authors: HashMap<&'a str, Vec<usize>>,
+    sources: HashMap<&'a str, Vec<usize>>,
+    // how many quotes each source has before any filtering, for `Weight::Rare`
+    source_counts: HashMap<&'a str, usize>,
+}
+
+impl<'a> Database<'a> {
+    /// Indexes the quotes by their time, skipping any without a valid `HH:MM` time
+    pub fn new(quotes: &'a [Quote]) -> Self {
+        let mut db = Self::index(quotes.iter(), 0, HashMap::new());
+        db.source_counts = db
+            .sources
+            .iter()
+            .map(|(source, quotes)| (*source, quotes.len()))
+            .collect();
+        db
+    }
+
+    /// A database of only the quotes that match the predicate.
+    ///
+    /// A source is still as rare as it was among all of the quotes
+    pub fn filtered(&self, predicate: impl Fn(&Quote) -> bool) -> Self {
+        Self::index(
+            self.quotes().filter(|q| predicate(q)),
+            self.skipped,
+            self.source_counts.clone(),
+        )
+    }
+
+    fn index(
+        quotes: impl Iterator<Item = &'a Quote>,
+        mut skipped: usize,
+        source_counts: HashMap<&'a str, usize>,
+    ) -> Self {
+        let mut map = MultiMap::new();
+        let mut indexed = vec![];
+        let (mut authors, mut sources) = (HashMap::new(), HashMap::new());
+        for quote in quotes {
+            match TimeKey::try_from(quote.time()) {
+                Ok(time) => {
+                    map.insert(time, quote);
+                    let pos = indexed.len();
+                    authors
+                        .entry(quote.author().trim())
+                        .or_insert_with(Vec::new)
+                        .push(pos);
+                    sources
+                        .entry(quote.source().trim())
+                        .or_insert_with(Vec::new)
+                        .push(pos);
+                    indexed.push(quote);
+                }
+                Err(..) => skipped += 1,
+            }
+        }
+        Self {
+            map,
+            quotes: indexed,
+            recent: vec![],
+            weight: Weight::Uniform,
+            max_candidates: None,
+            skipped,
+            authors,
+            sources,
+            source_counts,
+        }
+    }
+
+    /// All of the quotes, in the order they were loaded
+    pub fn quotes(&self) -> impl Iterator<Item = &'a Quote> + '_ {
+        self.quotes.iter().copied()
+    }
+
+    /// All of the quotes ordered by their time.
+    ///
+    /// Quotes for the same minute are in the order they were loaded
+    pub fn iter_chronological(&self) -> impl Iterator<Item = (TimeKey, &'a Quote)> + '_ {
+        let mut times = self.map.keys().copied().collect::<Vec<_>>();
+        times.sort();
+        times.into_iter().flat_map(move |time| {
+            self.map
+                .get_vec(&time)
+                .into_iter()
+                .flatten()
+                .map(move |quote| (time, *quote))
+        })
+    }
+
+    /// Picks any quote, regardless of its time
+    pub fn random(&self, rng: &mut impl Rng) -> Option<&'a Quote> {
+        self.choose(&self.quotes, rng)
+    }
+
+    /// Sets the quotes that were shown recently, so they're avoided when
+    /// there are others to choose from
+    pub fn set_recent(&mut self, recent: impl IntoIterator<Item = &'a Quote>) {
+        self.recent = recent.into_iter().collect();
+    }
+
+    /// Sets how likely each quote is to be picked
+    pub fn set_weight(&mut self, weight: Weight) {
+        self.weight = weight;
+    }
+
+    /// Only considers the first `max` quotes for a time, in the order they
+    /// were loaded, when picking one
+    pub fn set_max_candidates(&mut self, max: Option<usize>) {
+        self.max_candidates = max;
+    }
+
+    /// Each distinct author with how many quotes they have, most quotes first
+    pub fn authors(&self) -> Vec<(&'a str, usize)> {
+        Self::tally(&self.authors)
+    }
+
+    /// Each distinct source with how many quotes it has, most quotes first
+    pub fn sources(&self) -> Vec<(&'a str, usize)> {
+        Self::tally(&self.sources)
+    }
+
+    fn tally(index: &HashMap<&'a str, Vec<usize>>) -> Vec<(&'a str, usize)> {
+        let mut counts = index
+            .iter()
+            .map(|(name, quotes)| (*name, quotes.len()))
+            .collect::<Vec<_>>();
+        counts.sort_by(|(a, i), (b, j)| j.cmp(i).then_with(|| a.cmp(b)));
+        counts
+    }
+
+    /// The quotes by exactly this author, ignoring surrounding whitespace
+    pub fn by_author(&self, author: &str) -> Vec<&'a Quote> {
+        self.lookup(&self.authors, author)
+    }
+
+    /// The quotes from exactly this source, ignoring surrounding whitespace
+    pub fn by_source(&self, source: &str) -> Vec<&'a Quote> {
+        self.lookup(&self.sources, source)
+    }
+
+    fn lookup(&self, index: &HashMap<&'a str, Vec<usize>>, name: &str) -> Vec<&'a Quote> {
+        index
+            .get(name.trim())
+            .into_iter()
+            .flatten()
+            .map(|&pos| self.quotes[pos])
+            .collect()
+    }
+
+    /// Finds the quotes where the field contains the query, ignoring case
+    pub fn search(&self, query: &str, field: SearchField) -> Vec<&'a Quote> {
+        let query = query.to_lowercase();
+        let contains = |s: &str| s.to_lowercase().contains(&query);
+
+        // only the distinct names have to be checked for the author or source
+        let index = match field {
+            SearchField::All => {
+                return self
+                    .quotes()
+                    .filter(|q| contains(q.quote()) || contains(q.author()) || contains(q.source()))
+                    .collect()
+            }
+            SearchField::Author => &self.authors,
+            SearchField::Source => &self.sources,
+        };
+
+        let mut found = index
+            .iter()
+            .filter(|(name, _)| contains(name))
+            .flat_map(|(_, quotes)| quotes.iter().copied())
+            .collect::<Vec<_>>();
+        found.sort();
+        found.into_iter().map(|pos| self.quotes[pos]).collect()
+    }
+
+    /// Like `new`, but fails if none of the quotes could be indexed
+    pub fn try_new(quotes: &'a [Quote]) -> Result<Self, Error> {
+        let db = Self::new(quotes);
+        if db.is_empty() {
+            return Err(Error::NoQuotes);
+        }
+        Ok(db)
+    }
+
+    /// How many quotes there are in total
+    pub fn len(&self) -> usize {
+        self.quotes.len()
+    }
+
+    /// Whether there are no quotes at all
+    pub fn is_empty(&self) -> bool {
+        self.quotes.is_empty()
+    }
+
+    /// Counts rows that were skipped before the quotes got here, e.g. ones
+    /// that `load_quotes_counted` couldn't read
+    pub fn add_skipped(&mut self, rows: usize) {
+        self.skipped += rows;
+    }
+
+    /// How many quotes were skipped because of a malformed time, along with
+    /// any added by `add_skipped`
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+
+    /// Finds a quote for the time, or the closest time in the given direction.
+    ///
+    /// Returns `None` if no time in the entire day has a quote
+    pub fn around_time(
+        &self,
+        time: TimeKey,
+        dir: Direction,
+        rng: &mut impl Rng,
+    ) -> Option<&'a Quote> {
+        let mut time = time;
+
+        for _ in 0..MINUTES_PER_DAY {
+            match self.at_time(time, rng) {
+                Some(quote) => return Some(quote),
+                None => {
+                    time = match dir {
+                        Direction::Forward => time.succ(),
+                        Direction::Backward => time.pred(),
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Finds a quote for the closest time that has one, searching both directions.
+    ///
+    /// When two times are equally close, the later one is chosen
+    pub fn nearest(&self, time: TimeKey, rng: &mut impl Rng) -> Option<&'a Quote> {
+        let (mut later, mut earlier) = (time, time);
+
+        for _ in 0..=MINUTES_PER_DAY / 2 {
+            if let Some(quote) = self.at_time(later, rng) {
+                return Some(quote);
+            }
+            if let Some(quote) = self.at_time(earlier, rng) {
+                return Some(quote);
+            }
+            later = later.succ();
+            earlier = earlier.pred();
+        }
+        None
+    }
+
+    /// Picks a quote for the time.
+    ///
+    /// Quotes whose context can be highlighted are preferred over ones where it
+    /// can't, and then ones that weren't shown recently
+    pub fn at_time(&self, time: TimeKey, rng: &mut impl Rng) -> Option<&'a Quote> {
+        self.choose(self.candidates(time), rng)
+    }
+
+    /// The quotes for the time that are considered when picking one
+    pub fn candidates(&self, time: TimeKey) -> &[&'a Quote] {
+        let quotes = self.all_at(time);
+        match self.max_candidates {
+            Some(max) => &quotes[..max.min(quotes.len())],
+            None => quotes,
+        }
+    }
+
+    fn choose(&self, quotes: &[&'a Quote], rng: &mut impl Rng) -> Option<&'a Quote> {
+        // narrows down the choices, unless that would leave nothing to choose from
+        let prefer = |quotes: Vec<&'a Quote>, keep: &dyn Fn(&Quote) -> bool| {
+            let kept = quotes
+                .iter()
+                .copied()
+                .filter(|q| keep(q))
+                .collect::<Vec<_>>();
+            if kept.is_empty() {
+                quotes
+            } else {
+                kept
+            }
+        };
+
+        let quotes = prefer(quotes.to_vec(), &Quote::is_highlightable);
+        let quotes = prefer(quotes, &|q| !self.recent.contains(&q));
+        match self.weight {
+            Weight::Uniform => quotes.choose(rng).copied(),
+            // a quote is as likely as all of the quotes from a source with one quote
+            Weight::Rare => quotes
+                .choose_weighted(rng, |q| {
+                    let count = self.source_counts.get(q.source().trim()).map_or(1, |&n| n);
+                    1.0 / count as f64
+                })
+                .ok()
+                .copied(),
+        }
+    }
+
+    /// Every quote from `start` through to `end`, in order of time.
+    ///
+    /// If `end` is before `start`, the range wraps around past midnight
+    pub fn in_range(&self, start: TimeKey, end: TimeKey) -> Vec<(TimeKey, &'a Quote)> {
+        let mut quotes = vec![];
+        let mut time = start;
+        for _ in 0..MINUTES_PER_DAY {
+            quotes.extend(self.all_at(time).iter().map(|quote| (time, *quote)));
+            if time == end {
+                break;
+            }
+            time = time.succ();
+        }
+        quotes
+    }
+
+    /// The quotes whose text is found under more than one time, with each of
+    /// those times in order.
+    ///
+    /// The text is compared ignoring case and whitespace
+    pub fn duplicates(&self) -> Vec<(&'a Quote, Vec<TimeKey>)> {
+        let normalized = |q: &Quote| {
+            let words = q.quote().split_whitespace().collect::<Vec<_>>();
+            words.join(" ").to_lowercase()
+        };
+
+        let mut groups: Vec<(&'a Quote, Vec<TimeKey>)> = vec![];
+        let mut seen = HashMap::new();
+        for (time, quote) in self.iter_chronological() {
+            let pos = *seen.entry(normalized(quote)).or_insert_with(|| {
+                groups.push((quote, vec![]));
+                groups.len() - 1
+            });
+            let times = &mut groups[pos].1;
+            if times.last() != Some(&time) {
+                times.push(time);
+            }
+        }
+        groups.retain(|(_, times)| times.len() > 1);
+        groups
+    }
+
+    /// Every quote for the time, in the order they were loaded
+    pub fn all_at(&self, time: TimeKey) -> &[&'a Quote] {
+        self.map.get_vec(&time).map_or(&[], Vec::as_slice)
+    }
+
+    /// The `n`th quote for the time, in the order they were loaded
+    pub fn at_time_index(&self, time: TimeKey, n: usize) -> Option<&'a Quote> {
+        self.map.get_vec(&time).and_then(|q| q.get(n)).copied()
+    }
+
+    /// How many quotes there are for the time
+    pub fn count_at(&self, time: TimeKey) -> usize {
+        self.map.get_vec(&time).map_or(0, Vec::len)
+    }
+}
+
+/// Which part of a quote to search
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SearchField {
+    All,
+    Author,
+    Source,
+}
+
+/// How likely each of the quotes for a time is to be picked
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Weight {
+    /// Every quote is as likely as any other
+    Uniform,
+    /// A quote is less likely the more quotes its source has
+    Rare,
+}
+
+impl std::str::FromStr for Weight {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "uniform" => Ok(Weight::Uniform),
+            "rare" => Ok(Weight::Rare),
+            _ => Err(String::from("Unknown weight, available: uniform, rare")),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Direction {
+    Forward,
+    Backward,
+}
+
+impl std::str::FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "forward" => Ok(Direction::Forward),
+            "back" | "backward" => Ok(Direction::Backward),
+            _ => Err(String::from(
+                "Unknown direction, available: forward, backward",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(csv: &str) -> Vec<Quote> {
+        crate::load_quotes(csv.as_bytes()).unwrap()
+    }
+
+    fn quote(time: &str, text: &str, source: &str, author: &str) -> Quote {
+        Quote::new(time, "", text, source, author).unwrap()
+    }
+
+    fn time(s: &str) -> TimeKey {
+        TimeKey::try_from(s).unwrap()
+    }
+
+    /// Several quotes, each for its own minute
+    fn quotes_at(times: &[&str]) -> Vec<Quote> {
+        times
+            .iter()
+            .map(|&t| quote(t, &format!("At {}.", t), "Source", "Author"))
+            .collect()
+    }
+
+    #[test]
+    fn malformed_times_are_skipped() {
+        let quotes = load(concat!(
+            "24:00|midnight|At midnight.|Source|Author\n",
+            "1:2:3|one|At one.|Source|Author\n",
+            "noon|noon|At noon.|Source|Author\n",
+            "12:00|noon|At noon.|Source|Author\n",
+        ));
+        let db = Database::new(&quotes);
+        assert_eq!(db.len(), 1);
+        assert_eq!(db.skipped(), 3);
+        assert_eq!(db.quotes().next().unwrap().time(), "12:00");
+    }
+
+    #[test]
+    fn only_malformed_times_is_no_quotes() {
+        let quotes = load("24:00|midnight|At midnight.|Source|Author\n");
+        assert!(matches!(Database::try_new(&quotes), Err(Error::NoQuotes)));
+    }
+
+    #[test]
+    fn around_time_in_an_empty_database_is_none() {
+        let db = Database::new(&[]);
+        let mut rng = StdRng::seed_from_u64(0);
+        let time = TimeKey::new(12, 0).unwrap();
+        assert!(db.around_time(time, Direction::Forward, &mut rng).is_none());
+        assert!(db
+            .around_time(time, Direction::Backward, &mut rng)
+            .is_none());
+    }
+
+    #[test]
+    fn the_same_seed_picks_the_same_quote_for_a_minute() {
+        let quotes = quotes_at(&["12:00", "12:00", "12:00", "12:00", "12:00"]);
+        let db = Database::new(&quotes);
+        let pick = |seed| db.at_time(time("12:00"), &mut StdRng::seed_from_u64(seed));
+        for seed in 0..10 {
+            assert!(std::ptr::eq(pick(seed).unwrap(), pick(seed).unwrap()));
+        }
+    }
+
+    #[test]
+    fn the_daily_seed_picks_the_same_quote_all_day() {
+        let quotes = (0..10)
+            .map(|i| quote("12:00", &format!("Quote {}.", i), "Source", "Author"))
+            .collect::<Vec<_>>();
+        let db = Database::new(&quotes);
+        let noon = time("12:00");
+        let pick = |ordinal| {
+            let seed = crate::daily_seed(2024, ordinal, noon);
+            db.at_time(noon, &mut StdRng::seed_from_u64(seed)).unwrap()
+        };
+        assert_eq!(pick(100), pick(100));
+        assert!((101..120).any(|ordinal| pick(ordinal) != pick(100)));
+    }
+
+    #[test]
+    fn parse_direction() {
+        assert_eq!("forward".parse(), Ok(Direction::Forward));
+        assert_eq!("Backward".parse(), Ok(Direction::Backward));
+        assert_eq!("back".parse(), Ok(Direction::Backward));
+        assert!("sideways".parse::<Direction>().is_err());
+    }
+
+    #[test]
+    fn nearest_picks_the_closest_time_either_way() {
+        let quotes = quotes_at(&["06:00", "12:00", "12:30", "23:58"]);
+        let db = Database::new(&quotes);
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut nearest = |t| db.nearest(time(t), &mut rng).unwrap().time();
+
+        assert_eq!(nearest("12:00"), "12:00");
+        assert_eq!(nearest("12:10"), "12:00");
+        assert_eq!(nearest("12:20"), "12:30");
+        // a tie goes to the later time
+        assert_eq!(nearest("12:15"), "12:30");
+        // across midnight in both directions
+        assert_eq!(nearest("00:01"), "23:58");
+        assert_eq!(nearest("23:50"), "23:58");
+        assert_eq!(nearest("03:00"), "06:00");
+    }
+
+    #[test]
+    fn search_by_author() {
+        let quotes = vec![
+            quote("10:00", "It was ten.", "Bleak House", "Charles Dickens"),
+            quote(
+                "11:00",
+                "Reading Dickens at eleven.",
+                "Essays",
+                "Someone Else",
+            ),
+            quote("12:00", "Noon.", "Hard Times", "charles dickens "),
+            quote("13:00", "One.", "Emma", "Jane Austen"),
+        ];
+        let db = Database::new(&quotes);
+
+        let found = db.search("Dickens", SearchField::Author);
+        let sources = found.iter().map(|q| q.source()).collect::<Vec<_>>();
+        assert_eq!(sources, ["Bleak House", "Hard Times"]);
+
+        // the quote that only mentions him is found when searching everything
+        assert_eq!(db.search("dickens", SearchField::All).len(), 3);
+        assert!(db.search("Dickens", SearchField::Source).is_empty());
+    }
+
+    #[test]
+    fn counts_authors_and_sources() {
+        let quotes = vec![
+            quote("10:00", "Ten.", "Emma", "Jane Austen "),
+            quote("11:00", "Eleven.", "Persuasion", "Jane Austen"),
+            quote("12:00", "Noon.", "Emma ", "Jane Austen"),
+            quote("13:00", "One.", "Bleak House", "Charles Dickens"),
+        ];
+        let db = Database::new(&quotes);
+        assert_eq!(db.authors(), [("Jane Austen", 3), ("Charles Dickens", 1)]);
+        // a tie is in alphabetical order
+        assert_eq!(
+            db.sources(),
+            [("Emma", 2), ("Bleak House", 1), ("Persuasion", 1)]
+        );
+    }
+
+    #[test]
+    fn random_with_a_seed_is_the_same_pick() {
+        let quotes = quotes_at(&["01:00", "06:30", "12:00", "18:45", "23:59"]);
+        let db = Database::new(&quotes);
+        let pick = |seed| db.random(&mut StdRng::seed_from_u64(seed)).unwrap();
+        for seed in 0..10 {
+            assert!(std::ptr::eq(pick(seed), pick(seed)));
+        }
+        // any quote can be picked, not just ones near a certain time
+        let picked = (0..100).map(|seed| pick(seed).time()).collect::<Vec<_>>();
+        for quote in &quotes {
+            assert!(picked.contains(&quote.time()));
+        }
+        assert!(Database::new(&[])
+            .random(&mut StdRng::seed_from_u64(0))
+            .is_none());
+    }
+
+    #[test]
+    fn iter_chronological_is_in_order_of_time() {
+        let quotes = load(concat!(
+            "23:59|late|Late.|Source|First\n",
+            "12:00|noon|Noon.|Source|First\n",
+            "00:00|midnight|Midnight.|Source|Author\n",
+            "12:00|noon|Noon again.|Source|Second\n",
+            "06:15|morning|Morning.|Source|Author\n",
+            "12:00|noon|Noon, once more.|Source|Third\n",
+        ));
+        let db = Database::new(&quotes);
+        let ordered = db.iter_chronological().collect::<Vec<_>>();
+        assert_eq!(ordered.len(), quotes.len());
+        assert!(ordered.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+
+        // quotes for the same minute stay in the order they were loaded
+        let noon = ordered
+            .iter()
+            .filter(|(t, _)| *t == time("12:00"))
+            .map(|(_, q)| q.author())
+            .collect::<Vec<_>>();
+        assert_eq!(noon, ["First", "Second", "Third"]);
+    }
+
+    #[test]
+    fn at_time_index_is_in_the_order_they_were_loaded() {
+        let quotes = load(concat!(
+            "12:00|noon|First noon.|Source|Author\n",
+            "12:01|past|Just past.|Source|Author\n",
+            "12:00|noon|Second noon.|Source|Author\n",
+            "12:00|noon|Third noon.|Source|Author\n",
+        ));
+        let db = Database::new(&quotes);
+        let noon = time("12:00");
+        let at = |n| db.at_time_index(noon, n).map(Quote::quote);
+        assert_eq!(at(0), Some("First noon."));
+        assert_eq!(at(1), Some("Second noon."));
+        assert_eq!(at(2), Some("Third noon."));
+        assert_eq!(at(3), None);
+        assert_eq!(db.at_time_index(time("12:02"), 0), None);
+    }
+
+    #[test]
+    fn counts_the_quotes_at_a_time() {
+        let quotes = quotes_at(&["00:00", "12:00", "12:00", "12:00", "23:59"]);
+        let db = Database::new(&quotes);
+        assert_eq!(db.count_at(time("12:00")), 3);
+        assert_eq!(db.count_at(time("00:00")), 1);
+        assert_eq!(db.count_at(time("12:01")), 0);
+        assert_eq!(db.len(), 5);
+    }
+
+    #[test]
+    fn highlightable_quotes_are_preferred() {
+        let quotes = load(concat!(
+            "12:00|midnight|It was noon, not that.|Source|Wrong\n",
+            "12:00|noon|It was noon.|Source|Right\n",
+            "12:00|twelve|A dozen past eleven.|Source|Wrong\n",
+        ));
+        let db = Database::new(&quotes);
+        for seed in 0..20 {
+            let quote = db.at_time(time("12:00"), &mut StdRng::seed_from_u64(seed));
+            assert_eq!(quote.unwrap().author(), "Right");
+        }
+
+        // one that can't be highlighted is still better than nothing
+        let db = db.filtered(|q| q.author() == "Wrong");
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(db.at_time(time("12:00"), &mut rng).is_some());
+    }
+
+    #[test]
+    fn recent_quotes_arent_repeated() {
+        let quotes = load(concat!(
+            "12:00|noon|At noon, one.|Source|Author\n",
+            "12:00|noon|At noon, two.|Source|Author\n",
+            "12:00|noon|At noon, three.|Source|Author\n",
+        ));
+        let mut db = Database::new(&quotes);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        // every minute from noon lands on the quotes for noon
+        let mut shown: Vec<&Quote> = vec![];
+        for minute in 0..30 {
+            let now = TimeKey::new(12, minute).unwrap();
+            db.set_recent(shown.iter().rev().take(2).copied());
+            let quote = db.around_time(now, Direction::Backward, &mut rng).unwrap();
+            assert!(shown.iter().rev().take(2).all(|q| !std::ptr::eq(*q, quote)));
+            shown.push(quote);
+        }
+
+        // with nothing else to pick, a recent quote is picked again
+        let mut db = db.filtered(|q| q.quote() == "At noon, one.");
+        db.set_recent(db.quotes().collect::<Vec<_>>());
+        assert!(db.at_time(time("12:00"), &mut rng).is_some());
+    }
+
+    #[test]
+    fn in_range_includes_both_ends() {
+        let quotes = load(concat!(
+            "23:30|half past|Half past eleven.|Source|Author\n",
+            "12:00|noon|First noon.|Source|Author\n",
+            "11:59|almost|Almost noon.|Source|Author\n",
+            "00:30|half past|Half past midnight.|Source|Author\n",
+            "12:00|noon|Second noon.|Source|Author\n",
+            "12:01|past|Just past.|Source|Author\n",
+            "13:00|one|One o'clock.|Source|Author\n",
+        ));
+        let db = Database::new(&quotes);
+        let range = |start, end| {
+            db.in_range(time(start), time(end))
+                .into_iter()
+                .map(|(t, q)| format!("{} {}", t, q.quote()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            range("11:59", "12:01"),
+            [
+                "11:59 Almost noon.",
+                "12:00 First noon.",
+                "12:00 Second noon.",
+                "12:01 Just past.",
+            ]
+        );
+        assert_eq!(range("12:00", "12:00").len(), 2);
+        assert!(range("14:00", "23:00").is_empty());
+    }
+
+    #[test]
+    fn in_range_wraps_around_midnight() {
+        let quotes = quotes_at(&["00:30", "01:00", "01:01", "12:00", "23:00", "23:59"]);
+        let db = Database::new(&quotes);
+        let times = db
+            .in_range(time("23:00"), time("01:00"))
+            .into_iter()
+            .map(|(t, _)| t.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(times, ["23:00", "23:59", "00:30", "01:00"]);
+        // the whole day, from where it starts
+        assert_eq!(db.in_range(time("12:00"), time("11:59")).len(), 6);
+        assert_eq!(
+            db.in_range(time("12:00"), time("11:59"))[0].0,
+            time("12:00")
+        );
+    }
+
+    #[test]
+    fn the_author_and_source_indexes_match_a_scan() {
+        let quotes = crate::default_quotes();
+        let db = Database::new(&quotes);
+        let scan = |keep: &dyn Fn(&Quote) -> bool| {
+            db.quotes()
+                .filter(|q| keep(q))
+                .map(|q| q as *const Quote)
+                .collect::<Vec<_>>()
+        };
+        let found = |quotes: Vec<&Quote>| {
+            quotes
+                .into_iter()
+                .map(|q| q as *const Quote)
+                .collect::<Vec<_>>()
+        };
+
+        for (author, count) in db.authors().into_iter().take(20) {
+            let scanned = scan(&|q| q.author().trim() == author);
+            assert_eq!(scanned.len(), count);
+            assert_eq!(found(db.by_author(author)), scanned);
+            assert_eq!(found(db.by_author(&format!(" {} ", author))), scanned);
+        }
+        for (source, _) in db.sources().into_iter().take(20) {
+            let scanned = scan(&|q| q.source().trim() == source);
+            assert_eq!(found(db.by_source(source)), scanned);
+        }
+        assert!(db.by_author("Nobody At All").is_empty());
+
+        // a filtered database has its own indexes
+        let filtered = db.filtered(|q| q.time() < "06:00");
+        let (author, _) = db.authors()[0];
+        let expected = scan(&|q| q.author().trim() == author && q.time() < "06:00");
+        assert_eq!(found(filtered.by_author(author)), expected);
+    }
+
+    #[test]
+    fn rare_weighting_favors_sources_with_fewer_quotes() {
+        // nine quotes from one source at noon, and one from another
+        let mut quotes = (0..9)
+            .map(|i| quote("12:00", &format!("Common {}.", i), "Common", "Author"))
+            .collect::<Vec<_>>();
+        quotes.push(quote("12:00", "Rare.", "Rare", "Author"));
+        // the common source has plenty of quotes at other times too
+        quotes
+            .extend((0..30).map(|i| quote("13:00", &format!("Later {}.", i), "Common", "Author")));
+
+        let mut db = Database::new(&quotes);
+        let rare_picks = |db: &Database| {
+            let mut rng = StdRng::seed_from_u64(7);
+            (0..1000)
+                .filter(|_| db.at_time(time("12:00"), &mut rng).unwrap().source() == "Rare")
+                .count()
+        };
+
+        // uniformly it's picked about one time in ten
+        let uniform = rare_picks(&db);
+        assert!(
+            (50..150).contains(&uniform),
+            "uniform picked it {} times",
+            uniform
+        );
+
+        // and far more often when it's as likely as all of the common
+        // source's 39 quotes together
+        db.set_weight(Weight::Rare);
+        let rare = rare_picks(&db);
+        assert!(rare > 700, "rare picked it {} times", rare);
+    }
+
+    #[test]
+    fn rare_weighting_counts_the_quotes_from_before_filtering() {
+        // one quote from each source by the author, but plenty more from the
+        // common source by someone else
+        let mut quotes = vec![
+            quote("12:00", "Common.", "Common", "Author"),
+            quote("12:00", "Rare.", "Rare", "Author"),
+        ];
+        quotes.extend((0..30).map(|i| quote("13:00", &format!("Later {}.", i), "Common", "Other")));
+
+        let mut db = Database::new(&quotes).filtered(|q| q.author() == "Author");
+        assert_eq!(db.len(), 2);
+        db.set_weight(Weight::Rare);
+        let mut rng = StdRng::seed_from_u64(7);
+        let rare = (0..1000)
+            .filter(|_| db.at_time(time("12:00"), &mut rng).unwrap().source() == "Rare")
+            .count();
+        // the common source still has 31 quotes, not the 1 that's left
+        assert!(rare > 900, "rare picked it {} times", rare);
+    }
+
+    #[test]
+    fn parse_weight() {
+        assert_eq!("uniform".parse(), Ok(Weight::Uniform));
+        assert_eq!("RARE".parse(), Ok(Weight::Rare));
+        assert!("heavy".parse::<Weight>().is_err());
+    }
+
+    #[test]
+    fn the_same_quote_under_several_minutes_is_a_duplicate() {
+        let quotes = load(concat!(
+            "19:15|seven|At a quarter past seven the post came.|Letters|Fay\n",
+            "07:15|seven|at a quarter  past SEVEN the post came.|Letters|Fay\n",
+            "08:00|eight|Eight, and the shop opened.|Shop|Gus\n",
+            "08:00|eight|Eight, and the shop opened.|Shop|Gus\n",
+            "09:30|half past nine|Half past nine.|Waiting|Hal\n",
+        ));
+        let db = Database::new(&quotes);
+        let duplicates = db.duplicates();
+        assert_eq!(duplicates.len(), 1);
+        let (quote, times) = &duplicates[0];
+        // the earliest is the one reported
+        assert_eq!(quote.time(), "07:15");
+        assert_eq!(times, &[time("07:15"), time("19:15")]);
+    }
+
+    #[test]
+    fn the_candidates_are_cut_down_to_the_cap() {
+        let quotes = (0..5)
+            .map(|i| quote("12:00", &format!("Noon, {}.", i), "Source", "Author"))
+            .collect::<Vec<_>>();
+        let mut db = Database::new(&quotes);
+        let noon = time("12:00");
+        assert_eq!(db.candidates(noon).len(), 5);
+
+        // the first ones, as they were loaded
+        db.set_max_candidates(Some(2));
+        let texts = |db: &Database| {
+            db.candidates(noon)
+                .iter()
+                .map(|q| q.quote().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(texts(&db), ["Noon, 0.", "Noon, 1."]);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let picked = db.at_time(noon, &mut rng).unwrap();
+            assert!(texts(&db).iter().any(|text| text == picked.quote()));
+        }
+        // everything else at the minute is still there
+        assert_eq!(db.count_at(noon), 5);
+
+        // a cap above how many there are changes nothing
+        db.set_max_candidates(Some(10));
+        assert_eq!(db.candidates(noon).len(), 5);
+        db.set_max_candidates(None);
+        assert_eq!(db.candidates(noon).len(), 5);
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+
+/// Errors produced while loading, indexing and picking quotes
+#[derive(Debug)]
+pub enum Error {
+    /// The CSV could not be parsed
+    Csv(csv::Error),
+    /// The JSON could not be parsed
+    Json(serde_json::Error),
+    /// A time was not a valid 24-hour `HH:MM` timestamp
+    InvalidTime(String),
+    /// There were no usable quotes
+    NoQuotes,
+    /// Something could not be read or written
+    Io(std::io::Error),
+    /// The config could not be parsed
+    Config(toml::de::Error),
+    /// A file could not be read or written, with what it was for
+    File(&'static str, String, std::io::Error),
+    /// A file had no usable quotes
+    NoQuotesIn(String),
+    /// The quotes failed a check, this many times
+    Problems(usize),
+    /// The flags or the config asked for something that can't be done
+    Usage(String),
+    /// There's no quote for what was asked for
+    NotFound(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Csv(err) => write!(f, "cannot parse the quotes: {}", err),
+            Error::Json(err) => write!(f, "cannot parse the quotes: {}", err),
+            Error::InvalidTime(time) => write!(f, "'{}' is not a valid 24-hour time", time),
+            Error::NoQuotes => write!(f, "no quotes could be loaded"),
+            Error::Io(err) => write!(f, "cannot read or write: {}", err),
+            Error::Config(err) => write!(f, "cannot parse the config: {}", err),
+            Error::File(what, path, err) => write!(f, "cannot {} '{}': {}", what, path, err),
+            Error::NoQuotesIn(path) => write!(f, "no usable quotes in '{}'", path),
+            Error::Problems(1) => write!(f, "the quotes have 1 problem"),
+            Error::Problems(problems) => write!(f, "the quotes have {} problems", problems),
+            Error::Usage(msg) | Error::NotFound(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Csv(err) => Some(err),
+            Error::Json(err) => Some(err),
+            Error::Io(err) => Some(err),
+            Error::Config(err) => Some(err),
+            Error::File(_, _, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(err: csv::Error) -> Self {
+        Error::Csv(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::Config(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+    use crate::{Config, Database, Quote, TimeKey};
+
+    /// A reader that always fails
+    struct Broken;
+
+    impl std::io::Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("broken"))
+        }
+    }
+
+    #[test]
+    fn csv_error_from_a_reader_that_fails() {
+        match crate::load_quotes(Broken) {
+            Err(Error::Csv(err)) => assert!(err.is_io_error()),
+            other => panic!("expected a CSV error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn json_error_from_malformed_json() {
+        let err = crate::load_quotes_json(&b"[{\"time\": "[..]).unwrap_err();
+        assert!(matches!(err, Error::Json(..)));
+    }
+
+    #[test]
+    fn invalid_time_from_a_bad_timestamp() {
+        let err = TimeKey::try_from("25:00").unwrap_err();
+        assert!(matches!(err, Error::InvalidTime(ref time) if time == "25:00"));
+        let err = Quote::new("noon", "", "", "", "").unwrap_err();
+        assert!(matches!(err, Error::InvalidTime(ref time) if time == "noon"));
+    }
+
+    #[test]
+    fn no_quotes_from_an_empty_database() {
+        let err = Database::try_new(&[]).err().unwrap();
+        assert!(matches!(err, Error::NoQuotes));
+    }
+
+    #[test]
+    fn io_error_from_a_missing_file() {
+        let err = crate::load_quotes_from_path("does/not/exist.csv").unwrap_err();
+        assert!(matches!(err, Error::Io(..)));
+        assert!(err.to_string().starts_with("cannot read or write: "));
+    }
+
+    #[test]
+    fn config_error_from_bad_toml() {
+        let err = Config::from_toml("width = \"wide\"").unwrap_err();
+        assert!(matches!(err, Error::Config(..)));
+        assert!(Config::from_toml("colour = \"red\"").is_err());
+    }
+
+    #[test]
+    fn messages_for_the_command_line() {
+        let err = Error::File(
+            "open the log",
+            "log.txt".into(),
+            std::io::Error::other("denied"),
+        );
+        assert_eq!(err.to_string(), "cannot open the log 'log.txt': denied");
+        assert!(std::error::Error::source(&err).is_some());
+        assert_eq!(
+            Error::NoQuotesIn("a.csv".into()).to_string(),
+            "no usable quotes in 'a.csv'"
+        );
+        assert_eq!(Error::Problems(1).to_string(), "the quotes have 1 problem");
+        assert_eq!(Error::Problems(3).to_string(), "the quotes have 3 problems");
+        assert_eq!(
+            Error::NotFound("no quote for 12:05".into()).to_string(),
+            "no quote for 12:05"
+        );
+    }
+}
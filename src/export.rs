@@ -0,0 +1,286 @@
+use termcolor::{Color, ColorSpec};
+
+use crate::{ColorSet, Quote};
+
+impl Quote {
+    /// Renders the quote as HTML, with the context in a `<mark>` and the
+    /// attribution in a `<cite>`
+    pub fn to_html(&self) -> String {
+        format!(
+            "<blockquote>\n  <p>{}</p>\n  <cite>{} – {}</cite>\n</blockquote>\n",
+            highlight(self, escape_html, "<mark>", "</mark>"),
+            escape_html(self.author().trim()),
+            escape_html(self.source().trim()),
+        )
+    }
+
+    /// Renders the quote as a Markdown blockquote, with the context in bold
+    /// and the attribution on an italic trailing line
+    pub fn to_markdown(&self) -> String {
+        format!(
+            "> {}\n>\n> *— {}, {}*\n",
+            highlight(self, escape_markdown, "**", "**"),
+            escape_markdown(self.author().trim()),
+            escape_markdown(self.source().trim()),
+        )
+    }
+
+    /// Renders the quote and attribution on a single line of Pango markup, for
+    /// status bars like waybar or i3blocks, in the colors of the set
+    pub fn to_pango(&self, colors: &ColorSet) -> String {
+        let span = |spec: &ColorSpec, text: &str| match pango_attributes(spec) {
+            attrs if attrs.is_empty() => text.to_string(),
+            attrs => format!("<span{}>{}</span>", attrs, text),
+        };
+
+        // each piece is kept on one line, and gets its own span so the
+        // context's color isn't nested inside of another
+        let piece = |spec, s: &str| span(spec, &escape_html(&s.replace(char::is_whitespace, " ")));
+        let text = self.quote();
+        let quote = match self.highlight_range() {
+            Some(range) => format!(
+                "{}{}{}",
+                piece(&colors.inactive, &text[..range.start]),
+                piece(&colors.highlight, &text[range.clone()]),
+                piece(&colors.inactive, &text[range.end..]),
+            ),
+            None => piece(&colors.inactive, text),
+        };
+
+        let attribution = format!(
+            "{} – {}",
+            escape_html(self.author().trim()),
+            escape_html(self.source().trim())
+        );
+        format!("{} {}", quote, span(&colors.active, &attribution))
+    }
+}
+
+/// The attributes for a Pango `<span>` in the colors and style of the spec,
+/// each with a leading space
+fn pango_attributes(spec: &ColorSpec) -> String {
+    let mut attrs = String::new();
+    if let Some(fg) = spec.fg().and_then(|c| hex(c, spec.intense())) {
+        attrs.push_str(&format!(" foreground='{}'", fg));
+    }
+    if let Some(bg) = spec.bg().and_then(|c| hex(c, spec.intense())) {
+        attrs.push_str(&format!(" background='{}'", bg));
+    }
+    if spec.bold() {
+        attrs.push_str(" weight='bold'");
+    }
+    if spec.italic() {
+        attrs.push_str(" style='italic'");
+    }
+    if spec.underline() {
+        attrs.push_str(" underline='single'");
+    }
+    attrs
+}
+
+/// The color as `#RRGGBB`, using the xterm palette for the terminal colors
+fn hex(color: &Color, intense: bool) -> Option<String> {
+    const PALETTE: [(u8, u8, u8); 16] = [
+        (0x00, 0x00, 0x00),
+        (0xcd, 0x00, 0x00),
+        (0x00, 0xcd, 0x00),
+        (0xcd, 0xcd, 0x00),
+        (0x00, 0x00, 0xee),
+        (0xcd, 0x00, 0xcd),
+        (0x00, 0xcd, 0xcd),
+        (0xe5, 0xe5, 0xe5),
+        (0x7f, 0x7f, 0x7f),
+        (0xff, 0x00, 0x00),
+        (0x00, 0xff, 0x00),
+        (0xff, 0xff, 0x00),
+        (0x5c, 0x5c, 0xff),
+        (0xff, 0x00, 0xff),
+        (0x00, 0xff, 0xff),
+        (0xff, 0xff, 0xff),
+    ];
+
+    let ansi = |n: u8| -> (u8, u8, u8) {
+        match n {
+            0..=15 => PALETTE[usize::from(n)],
+            16..=231 => {
+                let level = |i: u8| if i == 0 { 0 } else { 55 + i * 40 };
+                let n = n - 16;
+                (level(n / 36), level(n / 6 % 6), level(n % 6))
+            }
+            _ => {
+                let gray = 8 + (n - 232) * 10;
+                (gray, gray, gray)
+            }
+        }
+    };
+
+    let bright = if intense { 8 } else { 0 };
+    let (r, g, b) = match color {
+        Color::Black => ansi(bright),
+        Color::Red => ansi(1 + bright),
+        Color::Green => ansi(2 + bright),
+        Color::Yellow => ansi(3 + bright),
+        Color::Blue => ansi(4 + bright),
+        Color::Magenta => ansi(5 + bright),
+        Color::Cyan => ansi(6 + bright),
+        Color::White => ansi(7 + bright),
+        Color::Ansi256(n) => ansi(*n),
+        Color::Rgb(r, g, b) => (*r, *g, *b),
+        _ => return None,
+    };
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+/// Escapes the quote, wrapping the context between `open` and `close`
+fn highlight(quote: &Quote, escape: fn(&str) -> String, open: &str, close: &str) -> String {
+    let text = quote.quote();
+    match quote.highlight_range() {
+        Some(range) => format!(
+            "{}{}{}{}{}",
+            escape(&text[..range.start]),
+            open,
+            escape(&text[range.clone()]),
+            close,
+            escape(&text[range.end..]),
+        ),
+        None => escape(text),
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+fn escape_markdown(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        if let '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' = ch {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(context: &str, text: &str, source: &str, author: &str) -> Quote {
+        Quote::new("12:00", context, text, source, author).unwrap()
+    }
+
+    /// The text between the first `open` and the `close` after it
+    fn between<'a>(s: &'a str, open: &str, close: &str) -> &'a str {
+        let start = s.find(open).unwrap() + open.len();
+        let end = start + s[start..].find(close).unwrap();
+        &s[start..end]
+    }
+
+    #[test]
+    fn html_marks_exactly_the_context() {
+        let quote = quote(
+            "twelve o'clock",
+            "It was Twelve O'Clock & <late>.",
+            "Source ",
+            "A \"Quoted\" Author ",
+        );
+        let html = quote.to_html();
+        assert_eq!(html.matches("<mark>").count(), 1);
+        assert_eq!(between(&html, "<mark>", "</mark>"), "Twelve O&#39;Clock");
+        assert_eq!(
+            between(&html, "<p>", "</p>"),
+            "It was <mark>Twelve O&#39;Clock</mark> &amp; &lt;late&gt;."
+        );
+        assert_eq!(
+            between(&html, "<cite>", "</cite>"),
+            "A &quot;Quoted&quot; Author – Source"
+        );
+    }
+
+    #[test]
+    fn html_without_the_context_has_no_mark() {
+        let html = quote("midnight", "It was noon.", "Source", "Author").to_html();
+        assert!(!html.contains("<mark>"));
+        assert_eq!(between(&html, "<p>", "</p>"), "It was noon.");
+    }
+
+    #[test]
+    fn markdown_bolds_exactly_the_context() {
+        let markdown = quote("noon", "It was noon.", "Source", "Author").to_markdown();
+        assert_eq!(between(&markdown, "**", "**"), "noon");
+        assert_eq!(markdown, "> It was **noon**.\n>\n> *— Author, Source*\n");
+    }
+
+    #[test]
+    fn markdown_escapes_the_quote_so_only_the_context_is_bold() {
+        let quote = quote(
+            "noon",
+            "A *star* at noon, by_the `clock`.",
+            "The *Book*",
+            "An_Author",
+        );
+        let markdown = quote.to_markdown();
+        assert_eq!(
+            markdown,
+            concat!(
+                "> A \\*star\\* at **noon**, by\\_the \\`clock\\`.\n",
+                ">\n",
+                "> *— An\\_Author, The \\*Book\\**\n",
+            )
+        );
+        // every asterisk that isn't escaped is emphasis
+        let bold = markdown.replace("\\*", "").matches("**").count();
+        assert_eq!(bold, 2);
+    }
+
+    #[test]
+    fn pango_spans_the_context_in_the_highlight_color() {
+        let quote = quote(
+            "noon",
+            "It was noon & <quiet>,\nthey'd say.",
+            "Source",
+            "O'Brien",
+        );
+        let pango = quote.to_pango(&ColorSet::standard());
+        assert_eq!(
+            pango,
+            concat!(
+                "<span foreground='#e5e5e5'>It was </span>",
+                "<span foreground='#ff0000'>noon</span>",
+                "<span foreground='#e5e5e5'> &amp; &lt;quiet&gt;, they&#39;d say.</span> ",
+                "<span foreground='#ffffff'>O&#39;Brien – Source</span>",
+            )
+        );
+        assert!(!pango.contains('\n'));
+    }
+
+    #[test]
+    fn pango_attributes_for_the_style() {
+        let mut spec = ColorSpec::new();
+        spec.set_fg(Some(Color::Rgb(1, 2, 255)))
+            .set_bg(Some(Color::Ansi256(16 + 36 * 5)))
+            .set_bold(true)
+            .set_italic(true)
+            .set_underline(true);
+        assert_eq!(
+            pango_attributes(&spec),
+            " foreground='#0102ff' background='#ff0000' weight='bold' style='italic' underline='single'"
+        );
+        assert_eq!(hex(&Color::Ansi256(232), false), Some("#080808".into()));
+        // without any colors there's no span at all
+        let pango = quote("noon", "noon", "S", "A").to_pango(&ColorSet::default());
+        assert_eq!(pango, "noon A – S");
+    }
+}
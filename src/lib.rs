@@ -0,0 +1,308 @@
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+mod ascii;
+mod bookmarks;
+mod border;
+mod cache;
+mod censor;
+mod collate;
+mod config;
+mod database;
+mod error;
+mod export;
+mod quote;
+mod time;
+
+use self::quote::JsonQuote;
+
+pub use self::ascii::to_ascii;
+pub use self::bookmarks::Bookmarks;
+pub use self::border::Border;
+pub use self::cache::QuoteCache;
+pub use self::censor::Censor;
+pub use self::collate::Collation;
+pub use self::config::Config;
+pub use self::database::{Database, Direction, SearchField, Weight};
+pub use self::error::Error;
+pub use self::quote::{Align, ColorSet, Layout, Quote, QuoteBuilder};
+pub use self::time::TimeKey;
+
+// from https://github.com/JohannesNE/literature-clock
+// the quote on line 474 spans several lines, which is fine because it's quoted
+pub const ANNOTATED_CSV: &[u8] = include_bytes!("../etc/litclock_annotated.csv");
+
+/// Loads the quotes embedded in the binary
+pub fn default_quotes() -> Vec<Quote> {
+    load_quotes(ANNOTATED_CSV).expect("embedded quotes should be valid")
+}
+
+/// Derives a seed from the day of the year and the time, so each minute of a
+/// given day always picks the same quote
+pub fn daily_seed(year: i32, ordinal: u32, time: TimeKey) -> u64 {
+    (u64::from(year as u32) << 32)
+        | (u64::from(ordinal) << 16)
+        | (u64::from(time.hour()) << 8)
+        | u64::from(time.minute())
+}
+
+/// A CSV reader configured for the headerless, `|` delimited quote format.
+///
+/// A field can span several lines if it's wrapped in double quotes, with any
+/// double quotes inside of it doubled up
+pub fn csv_builder() -> csv::ReaderBuilder {
+    let mut builder = csv::ReaderBuilder::new();
+    builder
+        .delimiter(b'|')
+        .has_headers(false)
+        .quote(b'"')
+        .quoting(true)
+        .double_quote(true);
+    builder
+}
+
+/// A CSV writer for the same format that `csv_builder` reads
+pub fn csv_writer_builder() -> csv::WriterBuilder {
+    let mut builder = csv::WriterBuilder::new();
+    builder
+        .delimiter(b'|')
+        .has_headers(false)
+        .quote(b'"')
+        .double_quote(true);
+    builder
+}
+
+/// Loads quotes from a headerless, `|` delimited CSV, skipping any rows that
+/// aren't a quote
+pub fn load_quotes<R: Read>(reader: R) -> Result<Vec<Quote>, Error> {
+    load_quotes_counted(reader).map(|(quotes, _)| quotes)
+}
+
+/// Like `load_quotes`, but also says how many rows were skipped.
+///
+/// Only failing to read is an error, so one broken row doesn't lose the rest
+#[cfg(not(feature = "rayon"))]
+pub fn load_quotes_counted<R: Read>(reader: R) -> Result<(Vec<Quote>, usize), Error> {
+    let (mut quotes, mut skipped) = (vec![], 0);
+    for row in csv_builder().from_reader(reader).deserialize() {
+        match row {
+            Ok(quote) => quotes.push(quote),
+            Err(err) if err.is_io_error() => return Err(err.into()),
+            Err(..) => skipped += 1,
+        }
+    }
+    Ok((quotes, skipped))
+}
+
+/// Like `load_quotes`, but also says how many rows were skipped.
+///
+/// Only failing to read is an error, so one broken row doesn't lose the rest.
+/// The rows are read in order, and then parsed in parallel
+#[cfg(feature = "rayon")]
+pub fn load_quotes_counted<R: Read>(reader: R) -> Result<(Vec<Quote>, usize), Error> {
+    use rayon::prelude::*;
+
+    let (mut records, mut skipped) = (vec![], 0);
+    for record in csv_builder().from_reader(reader).into_records() {
+        match record {
+            Ok(record) => records.push(record),
+            Err(err) if err.is_io_error() => return Err(err.into()),
+            Err(..) => skipped += 1,
+        }
+    }
+    let rows = records
+        .par_iter()
+        .map(|record| record.deserialize(None).ok())
+        .collect::<Vec<Option<Quote>>>();
+    skipped += rows.iter().filter(|row| row.is_none()).count();
+    Ok((rows.into_iter().flatten().collect(), skipped))
+}
+
+/// Concatenates sets of quotes, keeping only the first of any exact duplicates
+pub fn merge_quotes(sets: impl IntoIterator<Item = Vec<Quote>>) -> Vec<Quote> {
+    let mut seen = HashSet::new();
+    let mut quotes = sets.into_iter().flatten().collect::<Vec<_>>();
+    quotes.retain(|quote| seen.insert(quote.clone()));
+    quotes
+}
+
+/// Loads quotes from a file, as JSON if it ends in `.json` and as CSV otherwise
+pub fn load_quotes_from_path(path: impl AsRef<Path>) -> Result<Vec<Quote>, Error> {
+    let format = FileFormat::from_path(path.as_ref());
+    load_quotes_from_path_as(path, format)
+}
+
+/// Loads quotes from a file in the given format, regardless of its extension
+pub fn load_quotes_from_path_as(
+    path: impl AsRef<Path>,
+    format: FileFormat,
+) -> Result<Vec<Quote>, Error> {
+    let file = std::fs::File::open(path)?;
+    load_quotes_as(std::io::BufReader::new(file), format)
+}
+
+/// Loads quotes in the given format
+pub fn load_quotes_as<R: Read>(reader: R, format: FileFormat) -> Result<Vec<Quote>, Error> {
+    load_quotes_counted_as(reader, format).map(|(quotes, _)| quotes)
+}
+
+/// Like `load_quotes_as`, but also says how many CSV rows were skipped.
+///
+/// A JSON file is parsed as a whole, so it either loads or it doesn't
+pub fn load_quotes_counted_as<R: Read>(
+    reader: R,
+    format: FileFormat,
+) -> Result<(Vec<Quote>, usize), Error> {
+    match format {
+        FileFormat::Csv => load_quotes_counted(reader),
+        FileFormat::Json => load_quotes_json(reader).map(|quotes| (quotes, 0)),
+    }
+}
+
+/// Loads quotes from a JSON array.
+///
+/// Each quote either has the same fields as the CSV, `time`, `context`, `quote`,
+/// `source` and `author`, or the fields used by the literature clock's JSON:
+/// `time`, `quote_first`, `quote_time_case`, `quote_last`, `title` and `author`
+pub fn load_quotes_json<R: Read>(reader: R) -> Result<Vec<Quote>, Error> {
+    let quotes: Vec<JsonQuote> = serde_json::from_reader(reader)?;
+    Ok(quotes.into_iter().map(Quote::from).collect())
+}
+
+/// The format of a file of quotes
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FileFormat {
+    Csv,
+    Json,
+}
+
+impl FileFormat {
+    /// Guesses the format from the file's extension, assuming CSV unless it's `.json`
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => FileFormat::Json,
+            _ => FileFormat::Csv,
+        }
+    }
+}
+
+impl std::str::FromStr for FileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(FileFormat::Csv),
+            "json" => Ok(FileFormat::Json),
+            _ => Err(String::from("Unknown file format, available: csv, json")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    #[test]
+    fn rows_that_arent_a_quote_are_skipped() {
+        let csv = concat!(
+            "12:00|noon|At noon.|Source|Author\n",
+            "12:01|a row|that's too short\n",
+            "12:02|a row|that's|too|long|by one\n",
+            "12:03|three|At three past.|Source|Author\n",
+        );
+        let (quotes, skipped) = load_quotes_counted(csv.as_bytes()).unwrap();
+        assert_eq!(quotes.len(), 2);
+        assert_eq!(skipped, 2);
+        assert_eq!(load_quotes(csv.as_bytes()).unwrap(), quotes);
+    }
+
+    #[test]
+    fn rows_keep_their_order() {
+        // enough rows that, with the rayon feature, they're parsed on several threads
+        let mut csv = String::new();
+        for i in 0..5000 {
+            csv.push_str(&format!("12:00|noon|Quote {}.|Source|Author\n", i));
+            if i % 100 == 0 {
+                csv.push_str("12:00|broken\n");
+            }
+        }
+        let (quotes, skipped) = load_quotes_counted(csv.as_bytes()).unwrap();
+        assert_eq!(skipped, 50);
+        let expected = (0..5000).map(|i| format!("Quote {}.", i));
+        assert!(quotes.iter().map(Quote::quote).eq(expected));
+    }
+
+    #[test]
+    fn merging_keeps_one_of_each_duplicate() {
+        let quote = |time, text| Quote::new(time, "", text, "Source", "Author").unwrap();
+        let first = vec![quote("12:00", "Noon."), quote("13:00", "One.")];
+        let second = vec![
+            quote("13:00", "One."),
+            quote("14:00", "Two."),
+            // the same text at another time is another quote
+            quote("15:00", "Noon."),
+        ];
+        let merged = merge_quotes(vec![first, second]);
+        let times = merged.iter().map(Quote::time).collect::<Vec<_>>();
+        assert_eq!(times, ["12:00", "13:00", "14:00", "15:00"]);
+    }
+
+    #[test]
+    fn a_quoted_field_can_span_lines() {
+        let csv = concat!(
+            "12:00|noon|\"It was noon.\n",
+            "She said \"\"again\"\", and it was.\"|Source|Author\n",
+            "12:01|past|Just past.|Source|Author\n",
+        );
+        let (quotes, skipped) = load_quotes_counted(csv.as_bytes()).unwrap();
+        assert_eq!(skipped, 0);
+        assert_eq!(quotes.len(), 2);
+        assert_eq!(
+            quotes[0].quote(),
+            "It was noon.\nShe said \"again\", and it was."
+        );
+        assert_eq!(quotes[0].source(), "Source");
+        assert_eq!(quotes[1].time(), "12:01");
+    }
+
+    #[test]
+    fn the_built_in_quote_that_spans_lines_is_one_quote() {
+        let quotes = default_quotes();
+        let quote = quotes
+            .iter()
+            .find(|q| q.time() == "08:17" && q.context() == "8.17 a.m.")
+            .unwrap();
+        assert!(quote.quote().contains("\n“Monday, July 1."));
+        assert!(quotes.iter().all(|q| TimeKey::try_from(q.time()).is_ok()));
+    }
+
+    #[test]
+    fn quotes_round_trip_through_json() {
+        let quotes = vec![
+            Quote::new("12:00", "noon", "It was \"noon\".", "Source", "Author").unwrap(),
+            Quote::new("23:59", "late", "Late,\nvery late.", "Other", "Someone").unwrap(),
+        ];
+        let json = serde_json::to_string(&quotes).unwrap();
+        assert_eq!(load_quotes_json(json.as_bytes()).unwrap(), quotes);
+    }
+
+    #[test]
+    fn json_in_the_literature_clock_shape() {
+        let json = r#"[{
+            "time": "12:00",
+            "quote_first": "It was ",
+            "quote_time_case": "noon",
+            "quote_last": " exactly.",
+            "title": "Source",
+            "author": "Author"
+        }]"#;
+        let quotes = load_quotes_json(json.as_bytes()).unwrap();
+        let expected = Quote::new("12:00", "noon", "It was noon exactly.", "Source", "Author");
+        assert_eq!(quotes, [expected.unwrap()]);
+    }
+}
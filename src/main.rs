@@ -1,271 +1,3093 @@
-#![allow(dead_code)]
-use std::io::Write;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 
 use chrono::prelude::*;
-use multimap::MultiMap;
+use chrono_tz::Tz;
+use clap::{App, Arg, ArgMatches, ArgSettings, Shell, SubCommand};
 use rand::prelude::*;
-use serde::Deserialize;
 use termcolor::{Buffer, BufferWriter, Color, ColorChoice, ColorSpec, WriteColor};
+use terminal_size::{Height, Width};
 
-// from https://github.com/JohannesNE/literature-clock
-// line 474, in the source, should be on a single line
-const ANNOTATED_CSV: &[u8] = include_bytes!("../etc/litclock_annotated.csv");
-
-#[derive(Debug, Clone, Deserialize, PartialEq)]
-struct Quote {
-    time: String,
-    context: String,
-    quote: String,
-    source: String,
-    author: String,
-}
-
-impl Quote {
-    fn format(
-        &self,
-        stream: &mut Buffer,
-        colors: &ColorSet,
-        width: usize,
-    ) -> Result<(), std::io::Error> {
-        let quote = textwrap::Wrapper::new(width)
-            .initial_indent("  ")
-            .subsequent_indent("    ")
-            .wrap(&self.quote.replace('’', "\'"))
-            .join("\n");
-
-        writeln!(stream)?;
-
-        let ctx = self.context.replace('’', "\'").to_ascii_lowercase();
-
-        let mut head = false;
-        let mut highlights = vec![];
-
-        for (i, ch) in quote.chars().enumerate() {
-            if ch == '\n' {
-                head = true;
-                continue;
+use literal::{
+    Bookmarks, Border, Censor, Collation, ColorSet, Config, Database, Direction, Error, FileFormat,
+    Layout, Quote, QuoteCache, SearchField, TimeKey, Weight,
+};
+
+// what the exit status means, these are listed in the help too
+const EXIT_USAGE: i32 = 2;
+const EXIT_QUOTES: i32 = 3;
+const EXIT_NOT_FOUND: i32 = 4;
+const EXIT_IO: i32 = 5;
+
+const EXIT_STATUS: &str = "EXIT STATUS:
+    0    everything went fine
+    2    the flags or the config were wrong
+    3    the quotes couldn't be parsed or loaded, or failed validate or lint --strict
+    4    no quote was found, e.g. for --exact or after filtering
+    5    something couldn't be read or written";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{}", err);
+        std::process::exit(exit_code(&err));
+    }
+}
+
+fn exit_code(err: &Error) -> i32 {
+    match err {
+        Error::Csv(err) if err.is_io_error() => EXIT_IO,
+        Error::Json(err) if err.is_io() => EXIT_IO,
+        Error::Csv(..)
+        | Error::Json(..)
+        | Error::InvalidTime(..)
+        | Error::NoQuotes
+        | Error::NoQuotesIn(..)
+        | Error::Problems(..) => EXIT_QUOTES,
+        Error::Config(..) | Error::Usage(..) => EXIT_USAGE,
+        Error::NotFound(..) => EXIT_NOT_FOUND,
+        Error::Io(..) | Error::File(..) => EXIT_IO,
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let matches = match app().get_matches_safe() {
+        Ok(matches) => matches,
+        Err(err) => match err.kind {
+            clap::ErrorKind::HelpDisplayed | clap::ErrorKind::VersionDisplayed => {
+                println!("{}", err.message);
+                return Ok(());
             }
+            _ => return Err(Error::Usage(err.message)),
+        },
+    };
+    let (name, sub) = matches.subcommand();
+    let flags = Flags { top: &matches, sub };
+    let config = load_config(flags.value_of("config"))?;
+
+    let files = match flags.values_of("file") {
+        Some(paths) => Some(paths.collect::<Vec<_>>()),
+        None => config.file.as_deref().map(|path| vec![path]),
+    };
+
+    // these don't need the quotes to be loaded
+    match (name, sub) {
+        ("completions", Some(sub)) => return completions(sub),
+        ("dump-csv", _) => return dump_csv(flags),
+        ("man", _) => return Ok(man_page(&mut std::io::stdout())?),
+        ("validate", _) => return validate_files(files.as_deref()),
+        _ => {}
+    }
 
-            let z = ch.to_ascii_lowercase();
-            if Some(z) == ctx.chars().nth(highlights.len()) {
-                highlights.push(i);
-                if highlights.len() == ctx.len() {
-                    break;
+    let mut options = Options::new(flags, &config)?;
+
+    // with --mask the words are hidden, otherwise the quotes with them are left out
+    let censor = match flags.value_of("censor") {
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(words) => Some(Censor::new(&words)),
+            Err(err) => return Err(Error::File("read the words to censor", path.into(), err)),
+        },
+        None if flags.is_present("censor") => Some(Censor::builtin()),
+        None => None,
+    };
+
+    let (quotes, unreadable) = load_quotes(flags, files, censor.as_ref())?;
+    let mut db = Database::try_new(&quotes)?;
+    db.add_skipped(unreadable);
+    if options.verbose > 0 {
+        eprintln!(
+            "loaded {}, skipped {}",
+            plural(db.len(), "quote"),
+            plural(db.skipped(), "malformed row")
+        );
+    }
+    let mut db = narrow(db, flags, censor.as_ref())?;
+
+    match (name, sub) {
+        ("coverage", Some(sub)) => coverage(&db, sub.is_present("counts"), options.twelve_hour),
+        ("count", Some(sub)) => count(&db, sub, &options),
+        ("lint", Some(sub)) => lint(&db, sub, options.twelve_hour)?,
+        ("duplicates", _) => duplicates(&db, &options),
+        ("stats", Some(sub)) => stats(&db, sub, options.twelve_hour),
+        ("list", Some(sub)) => list(&db, sub, &options),
+        ("bookmark", _) => bookmark(&db, &mut options)?,
+        ("export", Some(sub)) => export(&db, sub, &mut options)?,
+        // everything else renders the quotes
+        _ => {
+            let renderer = Renderer::new(flags, &config, &options)?;
+            match (name, sub) {
+                ("bookmarks", _) => bookmarks(&renderer)?,
+                ("range", Some(sub)) => range(&db, sub, &renderer)?,
+                ("search", Some(sub)) => search(&db, sub, &renderer)?,
+                _ => {
+                    let show = ShowOptions::new(flags, name, sub)?;
+                    show_quotes(&mut db, &mut options, &renderer, &show)?
                 }
-                continue;
             }
+        }
+    }
+    Ok(())
+}
 
-            if ch == ' ' && head {
-                continue;
-            }
-            highlights.clear();
-            head = false;
+/// The flags for the subcommand that was used, which can also be given before it
+#[derive(Copy, Clone)]
+struct Flags<'a> {
+    top: &'a ArgMatches<'a>,
+    sub: Option<&'a ArgMatches<'a>>,
+}
+
+impl<'a> Flags<'a> {
+    // a flag given after the subcommand wins, the defaults are the same for both
+    fn matches(self, name: &str) -> &'a ArgMatches<'a> {
+        match self.sub {
+            Some(sub) if sub.occurrences_of(name) > 0 => sub,
+            _ => self.top,
         }
+    }
 
-        for (i, ch) in quote.replace('\'', "’").chars().enumerate() {
-            if highlights.contains(&i) {
-                stream.set_color(&colors.highlight)?;
-            } else {
-                stream.set_color(&colors.inactive)?;
+    fn value_of(self, name: &str) -> Option<&'a str> {
+        self.matches(name).value_of(name)
+    }
+
+    fn values_of(self, name: &str) -> Option<clap::Values<'a>> {
+        self.matches(name).values_of(name)
+    }
+
+    fn is_present(self, name: &str) -> bool {
+        self.matches(name).is_present(name)
+    }
+
+    fn occurrences_of(self, name: &str) -> u64 {
+        self.matches(name).occurrences_of(name)
+    }
+}
+
+/// What every command that loads the quotes goes by to pick and print them
+struct Options {
+    /// Every `--at`, anything that only needs one time uses the first
+    ats: Vec<TimeKey>,
+    mode: Mode,
+    rng: StdRng,
+    daily: bool,
+    twelve_hour: bool,
+    /// `--ascii`, for the reports that don't go through the renderer
+    ascii: bool,
+    verbose: u64,
+    width: usize,
+    timezone: Timezone,
+    time_source: Box<dyn Clock>,
+}
+
+impl Options {
+    fn new(flags: Flags, config: &Config) -> Result<Self, Error> {
+        let ats = flags
+            .values_of("at")
+            .into_iter()
+            .flatten()
+            .map(|s| parse_timestamp(s).expect("validated timestamp"))
+            .collect();
+
+        let direction: Direction = setting(flags, "direction", &config.direction, is_direction)?
+            .unwrap()
+            .parse()
+            .expect("validated direction");
+
+        let mode = if flags.is_present("random") {
+            Mode::Random
+        } else if let Some(index) = flags.value_of("index") {
+            Mode::Index(index.parse().expect("validated index"))
+        } else if flags.is_present("exact") {
+            Mode::Exact
+        } else if flags.is_present("nearest") {
+            Mode::Nearest
+        } else {
+            Mode::Around(direction)
+        };
+
+        let rng = match flags.value_of("seed") {
+            Some(seed) => StdRng::seed_from_u64(seed.parse().expect("validated seed")),
+            None => StdRng::from_entropy(),
+        };
+
+        let timezone: Timezone = setting(flags, "timezone", &config.timezone, is_timezone)?
+            .map_or(Timezone::Local, |s| s.parse().expect("validated timezone"));
+
+        let time_source: Box<dyn Clock> = match flags.value_of("now") {
+            Some(s) => Box::new(Pretend::new(parse_now(s).expect("validated date"))),
+            None => Box::new(timezone),
+        };
+
+        let width = resolve_width(
+            setting(
+                flags,
+                "width",
+                &config.width.map(|w| w.to_string()),
+                is_width,
+            )?
+            .map(|s| s.parse().expect("validated width")),
+            terminal_size::terminal_size().map(|(Width(w), _)| usize::from(w)),
+        );
+
+        Ok(Self {
+            ats,
+            mode,
+            rng,
+            daily: flags.is_present("daily"),
+            twelve_hour: flags.is_present("12h"),
+            ascii: flags.is_present("ascii"),
+            verbose: flags.occurrences_of("verbose"),
+            width,
+            timezone,
+            time_source,
+        })
+    }
+
+    /// Prints a line of a report, transliterated with `--ascii`
+    fn report(&self, line: &str) {
+        if self.ascii {
+            println!("{}", literal::to_ascii(line));
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    /// The first `--at`, otherwise the current time
+    fn time(&self, now: &NaiveDateTime) -> TimeKey {
+        self.ats.first().copied().unwrap_or_else(|| time_of(now))
+    }
+
+    /// With `--daily` the quote for a minute is the same all day, so it's
+    /// picked with a seed for the day and the minute
+    fn seed_daily(&mut self, now: &NaiveDateTime, time: TimeKey) {
+        if self.daily {
+            let seed = literal::daily_seed(now.year(), now.ordinal(), time);
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+    }
+
+    fn pick<'a>(&mut self, db: &Database<'a>, time: TimeKey) -> Result<&'a Quote, Error> {
+        pick(db, self.mode, time, &mut self.rng, self.twelve_hour)
+    }
+}
+
+fn completions(matches: &ArgMatches) -> Result<(), Error> {
+    let shell: Shell = matches
+        .value_of("shell")
+        .unwrap()
+        .parse()
+        .expect("validated shell");
+    app().gen_completions_to(env!("CARGO_PKG_NAME"), shell, &mut std::io::stdout());
+    Ok(())
+}
+
+fn dump_csv(flags: Flags) -> Result<(), Error> {
+    match flags.value_of("output") {
+        Some(path) => std::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(flags.is_present("append"))
+            .truncate(!flags.is_present("append"))
+            .open(path)?
+            .write_all(literal::ANNOTATED_CSV)?,
+        None => std::io::stdout().write_all(literal::ANNOTATED_CSV)?,
+    }
+    Ok(())
+}
+
+/// Checks each of the files, or the built-in quotes if there are none
+fn validate_files(files: Option<&[&str]>) -> Result<(), Error> {
+    let problems = match files {
+        Some(paths) => {
+            let mut problems = 0;
+            for &path in paths {
+                println!("{}:", path);
+                problems += if path == "-" {
+                    validate(std::io::stdin())
+                } else {
+                    validate(std::fs::File::open(path)?)
+                };
             }
-            write!(stream, "{}", ch)?;
-            stream.reset()?;
+            problems
         }
+        None => validate(literal::ANNOTATED_CSV),
+    };
+    if problems > 0 {
+        println!("{} found", plural(problems, "problem"));
+        return Err(Error::Problems(problems));
+    }
+    println!("no problems found");
+    Ok(())
+}
 
-        writeln!(stream)?;
-        writeln!(stream)?;
+/// Loads the built-in quotes and the files, and changes them as the flags ask,
+/// returning them with how many rows couldn't be read as a quote
+fn load_quotes(
+    flags: Flags,
+    files: Option<Vec<&str>>,
+    censor: Option<&Censor>,
+) -> Result<(Vec<Quote>, usize), Error> {
+    // the built-in quotes are always used, unless --no-builtin is given
+    let mut sets = vec![];
+    if !flags.is_present("no-builtin") {
+        sets.push(literal::default_quotes());
+    } else if files.is_none() {
+        return Err(Error::Usage(String::from(
+            "--no-builtin needs a --file, or a file in the config, to load quotes from",
+        )));
+    }
+    let file_format = flags
+        .value_of("file-format")
+        .map(|s| s.parse().expect("validated file format"));
+    // rows that can't be read as a quote are counted along with the bad times
+    let mut unreadable = 0;
+    for path in files.into_iter().flatten() {
+        let (quotes, skipped) = load_file(
+            path.as_ref(),
+            file_format,
+            flags.is_present("no-cache"),
+            flags.is_present("refresh-cache"),
+        )?;
+        // the built-in quotes would hide that a file gave nothing
+        if Database::new(&quotes).is_empty() {
+            return Err(Error::NoQuotesIn(path.to_string()));
+        }
+        sets.push(quotes);
+        unreadable += skipped;
+    }
+    let mut quotes = literal::merge_quotes(sets);
 
-        let attrib = textwrap::Wrapper::new(width)
-            .initial_indent("        ")
-            .subsequent_indent("        ")
-            .wrap(&format!("{} – {}", self.author.trim(), self.source))
-            .join("\n");
+    // a context that's slightly off from what's in the quote can still be highlighted
+    let fuzzy = flags
+        .value_of("fuzzy")
+        .unwrap()
+        .parse()
+        .expect("validated fuzziness");
+    for quote in quotes.iter_mut().filter(|q| !q.is_highlightable()) {
+        if let Some(phrase) = quote.fuzzy_context(fuzzy).map(String::from) {
+            *quote = quote.with_context(phrase);
+        }
+    }
 
-        stream.set_color(&colors.active)?;
-        writeln!(stream, "{}", attrib)?;
-        stream.reset()
+    if let Some(censor) = censor.filter(|_| flags.is_present("mask")) {
+        quotes = quotes.iter().map(|q| censor.mask(q)).collect();
     }
 
-    fn format_no_wrap(&self, stream: &mut Buffer, colors: &ColorSet) -> Result<(), std::io::Error> {
-        let ctx = self.context.to_lowercase();
+    if flags.is_present("dedup-attribution") {
+        quotes = quotes.iter().map(Quote::dedup_attribution).collect();
+    }
 
-        let start = self.quote.to_lowercase().find(&ctx).unwrap();
-        let end = start + ctx.len();
+    // the quotes are changed before they're wrapped, so the highlight still lines up
+    if flags.is_present("ascii") {
+        quotes = quotes.iter().map(Quote::to_ascii).collect();
+    }
+    Ok((quotes, unreadable))
+}
 
-        writeln!(stream)?;
+/// Leaves out the quotes the filters don't match, failing if none are left
+fn narrow<'a>(
+    mut db: Database<'a>,
+    flags: Flags,
+    censor: Option<&Censor>,
+) -> Result<Database<'a>, Error> {
+    let none_left = |after| {
+        Err(Error::NotFound(format!(
+            "no quotes are left after {}",
+            after
+        )))
+    };
+    // a quote has to match the --author and --source filters, and then not
+    // match the excludes. so an exclude always wins over an include
+    let filter = |name| flags.value_of(name).map(str::to_lowercase);
+    let (author, source) = (filter("author"), filter("source"));
+    let (exclude_author, exclude_source) = (filter("exclude-author"), filter("exclude-source"));
 
-        stream.set_color(&colors.inactive)?;
-        write!(stream, "{}", &self.quote[..start])?;
+    let filters = [&author, &source, &exclude_author, &exclude_source];
+    if filters.iter().any(|f| f.is_some()) {
+        let contains = |field: &str, filter: &Option<String>| {
+            filter
+                .as_ref()
+                .map(|filter| field.to_lowercase().contains(filter))
+        };
+        db = db.filtered(|q| {
+            contains(q.author(), &author).unwrap_or(true)
+                && contains(q.source(), &source).unwrap_or(true)
+                && !contains(q.author(), &exclude_author).unwrap_or(false)
+                && !contains(q.source(), &exclude_source).unwrap_or(false)
+        });
+        if db.is_empty() {
+            return none_left("filtering by author and source");
+        }
+    }
 
-        stream.set_color(&colors.highlight)?;
-        write!(stream, "{}", &self.quote[start..end])?;
+    if let Some(censor) = censor.filter(|_| !flags.is_present("mask")) {
+        db = db.filtered(|q| censor.is_clean(q));
+        if db.is_empty() {
+            return none_left("censoring");
+        }
+    }
 
-        stream.set_color(&colors.inactive)?;
-        writeln!(stream, "{}", &self.quote[end..])?;
+    let length = |name| {
+        flags
+            .value_of(name)
+            .map(|s| s.parse::<usize>().expect("validated length"))
+    };
+    let (min_length, max_length) = (length("min-length"), length("max-length"));
+    if min_length.is_some() || max_length.is_some() {
+        let (min, max) = (min_length.unwrap_or(0), max_length.unwrap_or(usize::MAX));
+        db = db.filtered(|q| (min..=max).contains(&q.len()));
+        if db.is_empty() {
+            return none_left("filtering by length");
+        }
+    }
 
-        writeln!(stream)?;
+    db.set_weight(
+        flags
+            .value_of("weight")
+            .unwrap()
+            .parse()
+            .expect("validated weight"),
+    );
+    db.set_max_candidates(
+        flags
+            .value_of("max-candidates")
+            .map(|s| s.parse().expect("validated count")),
+    );
+    Ok(db)
+}
 
-        stream.set_color(&colors.active)?;
-        writeln!(stream, "{:>20} – {}", self.author.trim(), self.source)?;
+fn count(db: &Database, matches: &ArgMatches, options: &Options) {
+    if matches.is_present("total") {
+        println!("{}", db.len());
+    } else {
+        let now = options.time_source.now();
+        println!("{}", db.count_at(options.time(&now)));
+    }
+}
 
-        stream.reset()
+fn lint(db: &Database, matches: &ArgMatches, twelve_hour: bool) -> Result<(), Error> {
+    let mut failing = 0;
+    for (time, quote) in db.iter_chronological() {
+        if quote.highlight_range().is_none() {
+            failing += 1;
+            println!(
+                "{} {}: context '{}' does not appear in the quote",
+                time.format(twelve_hour),
+                quote.author().trim(),
+                quote.context()
+            );
+        }
     }
+    println!("{} of {} quotes cannot be highlighted", failing, db.len());
+    if failing > 0 && matches.is_present("strict") {
+        return Err(Error::Problems(failing));
+    }
+    Ok(())
 }
 
-struct Database<'a> {
-    map: MultiMap<(u8, u8), &'a Quote>,
+fn duplicates(db: &Database, options: &Options) {
+    let duplicates = db.duplicates();
+    for (quote, times) in &duplicates {
+        let times = times
+            .iter()
+            .map(|time| time.format(options.twelve_hour))
+            .collect::<Vec<_>>();
+        println!("{}", times.join(", "));
+        options.report(&format!(
+            "  {} – {}",
+            quote.first_line(options.width),
+            quote.author().trim()
+        ));
+    }
+    println!("quotes under more than one time: {}", duplicates.len());
 }
 
-impl<'a> Database<'a> {
-    pub fn new(quotes: &'a [Quote]) -> Self {
-        Self {
-            map: quotes
-                .iter()
-                .map(|q| (q, &q.time))
-                .map(|(q, t)| {
-                    let mut t = t.splitn(2, ':').map(|d| d.parse::<u8>().unwrap());
-                    ((t.next().unwrap(), t.next().unwrap()), q)
-                })
-                .collect(),
+fn list(db: &Database, matches: &ArgMatches, options: &Options) {
+    if matches.value_of("what") == Some("times") {
+        // the first quote for each minute, cut down to fit beside its time
+        let mut previous = None;
+        for (time, quote) in db.iter_chronological() {
+            if previous.replace(time) == Some(time) {
+                continue;
+            }
+            let time = time.format(options.twelve_hour);
+            let line = quote.first_line(options.width.saturating_sub(time.len() + 2));
+            options.report(&format!("{}  {} – {}", time, line, quote.author().trim()));
         }
+        return;
     }
 
-    pub fn around_time(&self, hh: u8, mm: u8, dir: Direction) -> &Quote {
-        let (mut hh, mut mm) = (hh, mm);
+    let mut counts = match matches.value_of("what") {
+        Some("authors") => db.authors(),
+        _ => db.sources(),
+    };
+    // names are otherwise in code point order, where `Éluard` comes after `Zola`
+    if let Some(collation) = collation(matches) {
+        collation.sort(&mut counts);
+    }
+    for (name, count) in counts {
+        println!("{:>4} {}", count, name);
+    }
+}
 
-        loop {
-            match self.at_time(hh, mm) {
-                Some(quote) => return quote,
-                None => {
-                    let (h, m) = Self::next_time(hh, mm, dir);
-                    hh = h;
-                    mm = m;
-                }
+fn bookmark(db: &Database, options: &mut Options) -> Result<(), Error> {
+    let bookmarks = user_bookmarks()?;
+    let now = options.time_source.now();
+    let quote = options.pick(db, options.time(&now))?;
+    if bookmarks.add(quote)? {
+        options.report(&format!(
+            "bookmarked {} – {}",
+            quote.author().trim(),
+            quote.source().trim()
+        ));
+    } else {
+        println!("already bookmarked");
+    }
+    Ok(())
+}
+
+fn export(db: &Database, matches: &ArgMatches, options: &mut Options) -> Result<(), Error> {
+    let export = match matches.value_of("as") {
+        Some("markdown") => Quote::to_markdown,
+        _ => Quote::to_html,
+    };
+
+    if matches.is_present("all") {
+        for (_, quote) in db.iter_chronological() {
+            print!("{}", export(quote));
+        }
+    } else {
+        let now = options.time_source.now();
+        print!("{}", export(options.pick(db, options.time(&now))?));
+    }
+    Ok(())
+}
+
+/// How the quotes are drawn, and where they're printed
+struct Renderer<'a> {
+    stream: BufferWriter,
+    color: ColorSet,
+    /// An author's own color is only used for their name and source
+    author_colors: HashMap<String, Color>,
+    layout: Layout,
+    border: Border,
+    json: bool,
+    pango: bool,
+    quote_only: bool,
+    attribution_only: bool,
+    no_wrap: bool,
+    ascii: bool,
+    twelve_hour: bool,
+    output: Option<&'a str>,
+    append: bool,
+}
+
+impl<'a> Renderer<'a> {
+    fn new(flags: Flags<'a>, config: &Config, options: &Options) -> Result<Self, Error> {
+        let json = flags.value_of("format") == Some("json");
+        let pango = flags.value_of("format") == Some("pango");
+        let output = flags.value_of("output");
+
+        let mut layout = Layout {
+            width: options.width,
+            margin: flags
+                .value_of("margin")
+                .map_or(0, |s| s.parse().expect("validated margin")),
+            indent: flags
+                .value_of("indent")
+                .map_or(2, |s| s.parse().expect("validated indent")),
+            align: flags
+                .value_of("attribution-align")
+                .unwrap()
+                .parse()
+                .expect("validated alignment"),
+            markers: None,
+            highlight_all: flags.is_present("highlight-all"),
+            attribution_width: flags
+                .value_of("max-width-author")
+                .map(|s| s.parse().expect("validated width")),
+        };
+
+        // each color starts out as the theme's, and then the flags for it are applied on top
+        let theme = setting(flags, "theme", &config.theme, is_theme)?.unwrap();
+        let theme = ColorSet::theme(theme).expect("validated theme");
+        let spec = |name, theme: ColorSpec, config| -> Result<_, Error> {
+            let mut spec = theme;
+            if let Some(color) = setting(flags, name, config, is_color)? {
+                spec.set_fg(Some(parse_color(color).expect("validated color")));
+            }
+            if let Some(color) = flags.value_of(&format!("{}-bg", name)) {
+                spec.set_bg(Some(parse_color(color).expect("validated color")));
+            }
+            if flags.is_present(&format!("{}-bold", name)) {
+                spec.set_bold(true);
+            }
+            if flags.is_present(&format!("{}-italic", name)) {
+                spec.set_italic(true);
+            }
+            Ok(spec)
+        };
+
+        let color = ColorSet {
+            highlight: spec("highlight", theme.highlight, &config.highlight)?,
+            inactive: spec("inactive", theme.inactive, &config.inactive)?,
+            active: spec("active", theme.active, &config.active)?,
+        };
+
+        let author_colors = setting(
+            flags,
+            "author-colors",
+            &config.author_colors,
+            is_author_colors,
+        )?
+        .map_or_else(HashMap::new, |s| {
+            parse_author_colors(s).expect("validated author colors")
+        });
+
+        // a file only gets colors when they're asked for
+        let choice =
+            if json || pango || output.is_some() && flags.value_of("color") != Some("always") {
+                ColorChoice::Never
+            } else {
+                color_choice(flags.value_of("color"))
+            };
+        if options.verbose > 0 {
+            eprintln!(
+                "width is {}, color is {:?}, timezone is {:?}",
+                options.width, choice, options.timezone
+            );
+        }
+        let stream = BufferWriter::stdout(choice);
+
+        // the markers stand in for the highlight, so they're only used without colors
+        if !stream.buffer().supports_color() {
+            layout.markers = flags.value_of("context-marker").map(split_marker);
+        }
+
+        Ok(Self {
+            stream,
+            color,
+            author_colors,
+            layout,
+            border: flags
+                .value_of("border")
+                .unwrap()
+                .parse()
+                .expect("validated border"),
+            json,
+            pango,
+            quote_only: flags.is_present("quote-only"),
+            attribution_only: flags.is_present("attribution-only"),
+            no_wrap: flags.is_present("no-wrap"),
+            ascii: flags.is_present("ascii"),
+            twelve_hour: options.twelve_hour,
+            output,
+            append: flags.is_present("append"),
+        })
+    }
+
+    fn buffer(&self) -> Buffer {
+        self.stream.buffer()
+    }
+
+    /// Whether the quotes are printed as text, with room for the times beside them
+    fn is_text(&self) -> bool {
+        !self.json && !self.pango
+    }
+
+    fn colors_for(&self, quote: &Quote) -> Cow<'_, ColorSet> {
+        match self
+            .author_colors
+            .get(&quote.author().trim().to_lowercase())
+        {
+            Some(&author) => {
+                let mut colors = self.color.clone();
+                colors.active.set_fg(Some(author));
+                Cow::Owned(colors)
             }
+            None => Cow::Borrowed(&self.color),
         }
     }
 
-    pub fn at_time(&self, hh: u8, mm: u8) -> Option<&Quote> {
-        self.map
-            .get_vec(&(hh, mm))
-            .map(|q| *q.choose(&mut thread_rng()).unwrap())
+    fn render(&self, buffer: &mut Buffer, quote: &Quote) -> std::io::Result<()> {
+        if self.json {
+            serde_json::to_writer(&mut *buffer, quote)?;
+            return writeln!(buffer);
+        }
+        if self.pango {
+            let markup = quote.to_pango(&self.colors_for(quote));
+            if self.ascii {
+                return writeln!(buffer, "{}", literal::to_ascii(&markup));
+            }
+            return writeln!(buffer, "{}", markup);
+        }
+
+        let color = self.colors_for(quote);
+        let layout = &self.layout;
+
+        let mut text = self.buffer();
+        if self.attribution_only {
+            quote.format_attribution(&mut text, &color, self.twelve_hour)?;
+        } else if self.quote_only && self.no_wrap {
+            quote.format_quote_no_wrap(&mut text, &color, layout)?;
+        } else if self.quote_only {
+            quote.format_quote(&mut text, &color, layout)?;
+        } else if self.no_wrap {
+            quote.format_no_wrap(&mut text, &color, layout)?;
+        } else {
+            quote.format(&mut text, &color, layout)?;
+        }
+        let drawn = self.border.draw(text.as_slice(), layout.width);
+        if self.ascii {
+            // the attribution's dash and the border are only added when it's rendered
+            return buffer
+                .write_all(literal::to_ascii(&String::from_utf8_lossy(&drawn)).as_bytes());
+        }
+        buffer.write_all(&drawn)
     }
 
-    fn next_time(hh: u8, mm: u8, dir: Direction) -> (u8, u8) {
-        use self::Direction::*;
-        match (dir, hh, mm) {
-            (Backward, 0, 0) => (23, 59),
-            (Backward, .., 0) => (hh - 1, 59),
-            (Backward, ..) => (hh, mm - 1),
+    fn print(&self, buffer: &Buffer) -> std::io::Result<()> {
+        self.print_to(&mut std::io::stdout(), buffer)
+    }
 
-            (Forward, 23, 59) => (0, 0),
-            (Forward, .., 59) => (hh + 1, 0),
-            (Forward, ..) => (hh, mm + 1),
+    /// Prints to `out` rather than stdout, unless there's an `--output`
+    fn print_to(&self, out: &mut dyn Write, buffer: &Buffer) -> std::io::Result<()> {
+        match self.output {
+            // each print replaces what was in the file, unless it's being appended to
+            Some(path) => std::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(self.append)
+                .truncate(!self.append)
+                .open(path)?
+                .write_all(buffer.as_slice()),
+            None => emit(out, buffer),
         }
     }
+
+    /// Writes the time a quote is listed under, above it
+    fn write_listed_time(&self, buffer: &mut Buffer, time: TimeKey) -> std::io::Result<()> {
+        writeln!(buffer)?;
+        buffer.set_color(&self.color.active)?;
+        write!(buffer, "{}", time.format(self.twelve_hour))?;
+        buffer.reset()
+    }
 }
 
-#[derive(Debug, Copy, Clone)]
-enum Direction {
-    Forward,
-    Backward,
+fn bookmarks(renderer: &Renderer) -> Result<(), Error> {
+    let mut buffer = renderer.buffer();
+    for quote in user_bookmarks()?.load()? {
+        renderer.render(&mut buffer, &quote)?;
+    }
+    renderer.print(&buffer)?;
+    Ok(())
 }
 
-#[derive(Debug, Clone)]
-struct ColorSet {
-    active: ColorSpec,
-    inactive: ColorSpec,
-    highlight: ColorSpec,
+fn range(db: &Database, matches: &ArgMatches, renderer: &Renderer) -> Result<(), Error> {
+    let time =
+        |name| parse_timestamp(matches.value_of(name).unwrap()).expect("validated timestamp");
+    let mut buffer = renderer.buffer();
+    for (time, quote) in db.in_range(time("start"), time("end")) {
+        if renderer.is_text() {
+            renderer.write_listed_time(&mut buffer, time)?;
+        }
+        renderer.render(&mut buffer, quote)?;
+    }
+    renderer.print(&buffer)?;
+    Ok(())
 }
 
-fn main() {
-    let clock = match std::env::args().nth(1) {
-        Some(ref s) if s == "clock" => true,
-        _ => false,
+fn search(db: &Database, matches: &ArgMatches, renderer: &Renderer) -> Result<(), Error> {
+    let query = matches.value_of("query").unwrap();
+    let field = if matches.is_present("author-only") {
+        SearchField::Author
+    } else if matches.is_present("source-only") {
+        SearchField::Source
+    } else {
+        SearchField::All
     };
 
-    // TODO make this customizable
-    let wait = 60;
-    let width = 60;
+    let mut buffer = renderer.buffer();
+    for quote in db.search(query, field) {
+        if renderer.is_text() {
+            let time = TimeKey::try_from(quote.time()).expect("indexed time");
+            renderer.write_listed_time(&mut buffer, time)?;
+        }
+        // the query is only highlighted, the data itself is left alone
+        if renderer.json {
+            renderer.render(&mut buffer, quote)?;
+        } else {
+            renderer.render(&mut buffer, &quote.with_context(query))?;
+        }
+    }
+    renderer.print(&buffer)?;
+    Ok(())
+}
+
+/// The flags for showing a quote with `once`, or for going on showing them
+/// with `clock` or `--every`
+struct ShowOptions<'a> {
+    clock: bool,
+    once: bool,
+    every: Option<Duration>,
+    interval: Duration,
+    jitter: Duration,
+    until: Option<TimeKey>,
+    clear: bool,
+    streaming: bool,
+    reading: bool,
+    show_time: bool,
+    dry_run: bool,
+    typewriter: Option<u32>,
+    log: Option<&'a str>,
+}
+
+impl<'a> ShowOptions<'a> {
+    fn new(flags: Flags<'a>, name: &str, sub: Option<&ArgMatches>) -> Result<Self, Error> {
+        let clock = sub.filter(|_| name == "clock");
+        let seconds = |name, default| {
+            clock
+                .and_then(|matches| matches.value_of(name))
+                .map_or(Duration::from_secs(default), |s| {
+                    Duration::from_secs(s.parse().expect("validated seconds"))
+                })
+        };
+        let reading = clock.is_some_and(|matches| matches.is_present("group-by-author"));
+        // --author and --at are given before the subcommand, where clap can't check them
+        if reading && !flags.is_present("author") {
+            return Err(Error::Usage(String::from(
+                "--group-by-author needs an --author to read through",
+            )));
+        }
+        if reading && flags.is_present("at") {
+            return Err(Error::Usage(String::from(
+                "--group-by-author can't be used with --at, the time has to change to turn the page",
+            )));
+        }
 
-    let mut highlight = ColorSpec::new();
-    highlight.set_fg(Some(Color::Red)).set_intense(true);
+        Ok(Self {
+            clock: clock.is_some(),
+            once: name == "once",
+            every: flags
+                .value_of("every")
+                .map(|s| Duration::from_secs(s.parse().expect("validated interval"))),
+            interval: seconds("interval", 60),
+            jitter: seconds("jitter", 0),
+            until: clock
+                .and_then(|matches| matches.value_of("until"))
+                .map(|s| parse_timestamp(s).expect("validated timestamp")),
+            clear: clock.is_some_and(|matches| matches.is_present("clear")),
+            streaming: clock.is_some_and(|matches| matches.is_present("stream")),
+            reading,
+            show_time: flags.is_present("show-time"),
+            dry_run: flags.is_present("dry-run"),
+            typewriter: flags
+                .value_of("typewriter")
+                .map(|s| s.parse().expect("validated typewriter speed")),
+            log: flags.value_of("log"),
+        })
+    }
+}
 
-    let mut inactive = ColorSpec::new();
-    inactive.set_fg(Some(Color::White)).set_intense(false);
+impl ShowOptions<'_> {
+    /// How long to wait before looking at the time again
+    fn delay(&self, now: &NaiveDateTime, rng: &mut impl Rng) -> Duration {
+        // wake up early if asked to, but never sleep past the start of the next minute
+        let wait = self.every.unwrap_or(self.interval);
+        if !self.clock {
+            return wait;
+        }
+        let next_minute = until_next_minute(now.second(), now.nanosecond());
+        // a stream only has something new to print once the minute changes
+        let wait = if self.streaming {
+            next_minute
+        } else {
+            next_minute.min(wait)
+        };
+        jittered(wait, next_minute, self.jitter, rng)
+    }
+}
 
-    let mut active = ColorSpec::new();
-    active.set_fg(Some(Color::White)).set_intense(true);
+/// Shows the quote for the time, for each `--at` time, or one after another as the time goes by
+fn show_quotes(
+    db: &mut Database,
+    options: &mut Options,
+    renderer: &Renderer,
+    show: &ShowOptions,
+) -> Result<(), Error> {
+    if show.dry_run {
+        return dry_run(db, options);
+    }
 
-    let color = ColorSet {
-        highlight,
-        inactive,
-        active,
+    // only opened once a quote is going to be shown, so nothing else creates the file
+    let log = match show.log {
+        Some(path) => {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path);
+            Some(file.map_err(|err| Error::File("open the log", path.into(), err))?)
+        }
+        None => None,
     };
 
-    fn load_quotes() -> Vec<Quote> {
-        csv::ReaderBuilder::new()
-            .delimiter(b'|')
-            .has_headers(false)
-            .from_reader(ANNOTATED_CSV)
-            .deserialize()
-            .filter_map(Result::ok)
-            .collect()
+    // several --at times print a quote for each of them in turn
+    if options.ats.len() > 1 && !show.clock && show.every.is_none() {
+        return show_each(db, options, renderer, show, log);
+    }
+
+    // stopping the clock between quotes leaves the terminal as it was found
+    if show.clock || show.every.is_some() && !show.once {
+        if let Err(err) = ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst)) {
+            eprintln!("cannot handle ctrl-c: {}", err);
+        }
+    }
+    keep_showing(db, options, renderer, show, log, &mut std::io::stdout())
+}
+
+fn dry_run(db: &Database, options: &mut Options) -> Result<(), Error> {
+    let now = options.time_source.now();
+    let time = options.time(&now);
+    options.seed_daily(&now, time);
+    let quote = options.pick(db, time)?;
+    let matched = TimeKey::try_from(quote.time()).expect("indexed time");
+    options.report(&format!(
+        "{} -> {} {} – {} ({})",
+        time.format(options.twelve_hour),
+        matched.format(options.twelve_hour),
+        quote.author().trim(),
+        quote.source().trim(),
+        plural(db.candidates(matched).len(), "candidate")
+    ));
+    Ok(())
+}
+
+/// Prints a quote for each of the `--at` times
+fn show_each(
+    db: &Database,
+    options: &mut Options,
+    renderer: &Renderer,
+    show: &ShowOptions,
+    mut log: Option<std::fs::File>,
+) -> Result<(), Error> {
+    let now = options.time_source.now();
+    let mut buffer = renderer.buffer();
+    for time in options.ats.clone() {
+        options.seed_daily(&now, time);
+        let quote = options.pick(db, time)?;
+        if show.show_time && renderer.is_text() {
+            write_time(
+                &mut buffer,
+                time,
+                quote,
+                &renderer.color,
+                options.twelve_hour,
+            )?;
+        }
+        renderer.render(&mut buffer, quote)?;
+        if let Some(log) = log.as_mut() {
+            write_log(log, now, quote, renderer.json)?;
+        }
     }
+    renderer.print(&buffer)?;
+    Ok(())
+}
 
-    let quotes = load_quotes();
-    let db = Database::new(&quotes);
+/// Shows the quote for the time, and with `clock` or `--every` the next one
+/// each time it changes
+fn keep_showing(
+    db: &mut Database,
+    options: &mut Options,
+    renderer: &Renderer,
+    show: &ShowOptions,
+    mut log: Option<std::fs::File>,
+    out: &mut dyn Write,
+) -> Result<(), Error> {
+    let (clock, once, every, until, clear) =
+        (show.clock, show.once, show.every, show.until, show.clear);
+    let (json, twelve_hour) = (renderer.json, options.twelve_hour);
 
-    let stream = BufferWriter::stdout(ColorChoice::Auto);
+    // picking the quotes has to go the same way with or without --jitter
+    let mut jitter_rng = options.rng.clone();
 
+    // the clock avoids repeating the last few quotes when a time has others
+    const RECENT: usize = 10;
+    let mut recent = std::collections::VecDeque::with_capacity(RECENT);
     let mut last = None;
+    let mut turn = 0;
+    let pages = if show.reading { pages(db) } else { vec![] };
+    let mut page = None;
     loop {
-        let now: DateTime<Local> = Local::now();
-        let (hh, mm) = (now.hour() as u8, now.minute() as u8);
+        let now = options.time_source.now();
+        let time = options.time(&now);
+
+        let rotation = match every {
+            Some(..) => db.all_at(time),
+            None => &[],
+        };
+
+        let quote = match last {
+            // each minute turns to the next quote, starting from the current time
+            _ if !pages.is_empty() => {
+                if last.is_none_or(|(shown, _)| shown != time) {
+                    page = Some(turn_page(&pages, page, time));
+                }
+                pages[page.unwrap()].1
+            }
+            // take turns showing each of the minute's quotes
+            _ if !rotation.is_empty() => {
+                if last.is_none_or(|(shown, _)| shown != time) {
+                    turn = 0;
+                }
+                turn += 1;
+                rotation[(turn - 1) % rotation.len()]
+            }
+            // keep the same quote until the minute changes
+            Some((shown, quote)) if shown == time => quote,
+            _ => {
+                if options.daily {
+                    options.seed_daily(&now, time);
+                } else {
+                    // the daily quote has to be the same for everyone, so it
+                    // can't depend on what was shown before
+                    db.set_recent(recent.iter().copied());
+                }
+
+                let quote = options.pick(db, time)?;
+                if options.verbose > 1 {
+                    let matched = TimeKey::try_from(quote.time()).expect("indexed time");
+                    match options.mode.walked(time, matched) {
+                        Some(steps) => {
+                            eprintln!(
+                                "{} matched {}, {} away",
+                                time,
+                                matched,
+                                plural(steps, "minute")
+                            )
+                        }
+                        None => eprintln!("{} matched {}", time, matched),
+                    }
+                }
+                if recent.len() == RECENT {
+                    recent.pop_front();
+                }
+                recent.push_back(quote);
+                quote
+            }
+        };
+        let mut rendered = renderer.buffer();
+        if show.show_time && renderer.is_text() {
+            write_time(&mut rendered, time, quote, &renderer.color, twelve_hour)?;
+        }
+        renderer.render(&mut rendered, quote)?;
+
+        // counting down shows how long is left under each quote
+        let remaining = until.map(|until| time.minutes_until(until));
+        let mut footer = renderer.buffer();
+        if let Some(remaining) = remaining.filter(|_| renderer.is_text()) {
+            write_remaining(&mut footer, remaining, &renderer.color)?;
+        }
+        rendered.write_all(footer.as_slice())?;
+
+        let mut buffer = renderer.buffer();
+        if clear && buffer.supports_color() {
+            clear_screen(&mut buffer, rendered.as_slice())?;
+        } else {
+            buffer = rendered;
+        }
+
+        let changed = last.is_none_or(|(_, prev)| prev != quote);
+        // the header shows the current time, so it has to be reprinted every
+        // minute, and a JSON line is written for every minute too
+        let every_minute = show.streaming || show.show_time || json || until.is_some();
+        match last.replace((time, quote)) {
+            Some((shown, prev)) if still_shown(shown, time, prev == quote, every_minute) => {}
+            // the animation is only for a terminal, anywhere else it'd just be slow.
+            // colors can be on when piped, so they don't say whether it's one
+            _ if show.typewriter.is_some()
+                && buffer.supports_color()
+                && renderer.output.is_none()
+                && std::io::stdout().is_terminal() =>
+            {
+                let mut header = renderer.buffer();
+                if clear {
+                    write!(header, "{}", CLEAR_SCREEN)?;
+                }
+                if show.show_time {
+                    write_time(&mut header, time, quote, &renderer.color, twelve_hour)?;
+                }
+                emit(out, &header)?;
 
-        // TODO add flag for approx time, and if so, which direction to search
-        let quote = db.around_time(hh, mm, Direction::Backward);
-        let mut buffer = stream.buffer();
-        quote.format(&mut buffer, &color, width).unwrap();
+                let cps = show.typewriter.unwrap();
+                if typewrite(out, renderer, quote, cps)? {
+                    let mut buffer = renderer.buffer();
+                    writeln!(buffer)?;
+                    restore_terminal(&mut buffer, clear)?;
+                    emit(out, &buffer)?;
+                    return Ok(());
+                }
+                emit(out, &footer)?;
+            }
+            _ => renderer.print_to(out, &buffer)?,
+        }
 
-        match last.replace(quote) {
-            Some(prev) if prev != quote => stream.print(&buffer).unwrap(),
-            None => stream.print(&buffer).unwrap(),
-            _ => (),
+        if let Some(log) = log.as_mut().filter(|_| changed) {
+            write_log(log, now, quote, json)?;
         }
 
-        if !clock {
-            return;
+        if once || !clock && every.is_none() || remaining == Some(0) {
+            if clear {
+                let mut buffer = renderer.buffer();
+                restore_terminal(&mut buffer, clear)?;
+                emit(out, &buffer)?;
+            }
+            return Ok(());
         }
 
-        let diff = (wait - now.second()).into();
-        let delta = std::time::Duration::from_secs(diff);
-        std::thread::sleep(delta);
+        if options.time_source.sleep(show.delay(&now, &mut jitter_rng)) {
+            let mut buffer = renderer.buffer();
+            restore_terminal(&mut buffer, clear)?;
+            emit(out, &buffer)?;
+            return Ok(());
+        }
     }
 }
 
-fn is_timestamp(val: String) -> Result<(), String> {
-    let err = String::from("The value must be a valid 24-hour timestamp, HH:MM");
+/// The quotes of each author that `--author` matched, in order of time, which
+/// are read through no matter what time they're for
+fn pages<'a>(db: &Database<'a>) -> Vec<(TimeKey, &'a Quote)> {
+    let mut pages = vec![];
+    for (author, _) in db.authors() {
+        pages.extend(db.by_author(author).into_iter().map(|quote| {
+            let time = TimeKey::try_from(quote.time()).expect("indexed time");
+            (time, quote)
+        }));
+    }
+    pages.sort_by_key(|&(time, _)| time);
+    pages
+}
 
-    let mut s = val
-        .split(':')
-        .map(|d| d.parse::<u8>().map_err(|_| err.clone()));
-    match (
-        s.next().ok_or_else(|| err.clone())??,
-        s.next().ok_or_else(|| err.clone())??,
-    ) {
-        ((0...23), (0...59)) => Ok(()),
-        _ => Err(err),
+/// The page after `page`, going back to the first after the last. Reading
+/// starts from the first quote for `time` or after it
+fn turn_page(pages: &[(TimeKey, &Quote)], page: Option<usize>, time: TimeKey) -> usize {
+    match page {
+        Some(page) => (page + 1) % pages.len(),
+        None => pages.iter().position(|&(at, _)| at >= time).unwrap_or(0),
     }
 }
 
-fn is_color(val: String) -> Result<(), String> {
-    const COLORS: [&str; 9] = [
-        "black", "blue", "green", "red", "cyan", "magenta", "yellow", "white", "grey",
+/// Whether the quote that was shown at `shown` can be left as it is at `time`,
+/// rather than printed again. It's at most once a minute, and only once the
+/// quote changes unless something else on it changes `every_minute`
+fn still_shown(shown: TimeKey, time: TimeKey, same_quote: bool, every_minute: bool) -> bool {
+    same_quote && (shown == time || !every_minute)
+}
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Sleeps for the duration, returning early with `true` if ctrl-c was pressed
+fn sleep_until_interrupted(delay: Duration) -> bool {
+    const STEP: Duration = Duration::from_millis(100);
+    let mut left = delay;
+    while !INTERRUPTED.load(Ordering::SeqCst) {
+        if left == Duration::from_secs(0) {
+            return false;
+        }
+        let step = left.min(STEP);
+        std::thread::sleep(step);
+        left -= step;
+    }
+    true
+}
+
+/// Resets the colors, and shows the cursor again if the screen was being cleared
+fn restore_terminal(buffer: &mut Buffer, clear: bool) -> std::io::Result<()> {
+    buffer.reset()?;
+    if clear && buffer.supports_color() {
+        write!(buffer, "\x1b[?25h")?;
+    }
+    Ok(())
+}
+
+const ABOUT: &str = "displays a quote from literature for the current time";
+
+fn app() -> App<'static, 'static> {
+    App::new(env!("CARGO_PKG_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .author(env!("CARGO_PKG_AUTHORS"))
+        .about(ABOUT)
+        .after_help(EXIT_STATUS)
+        .arg(
+            Arg::with_name("at")
+                .long("at")
+                .value_name("HH:MM")
+                .help("use this time instead of the current time, repeat it for a quote for each time")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .global(true)
+                .validator(is_timestamp),
+        )
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .value_name("path")
+                .help("read the defaults for the flags from this TOML file")
+                .takes_value(true)
+                .global(true),
+        )
+        .args(&render_args())
+        .args(&show_args())
+        .arg(
+            Arg::with_name("timezone")
+                .long("timezone")
+                .value_name("name|+HH:MM")
+                .help("use the current time in this timezone, e.g. Europe/Paris or +02:00")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .global(true)
+                .validator(is_timezone),
+        )
+        .arg(
+            Arg::with_name("now")
+                .long("now")
+                .value_name("YYYY-MM-DD HH:MM")
+                .help("pretend the clock was started at this date and time")
+                .takes_value(true)
+                .global(true)
+                .conflicts_with("timezone")
+                .validator(is_now),
+        )
+        .arg(
+            Arg::with_name("weight")
+                .long("weight")
+                .value_name("uniform|rare")
+                .help("how to pick between a time's quotes, rare favors sources with fewer quotes")
+                .takes_value(true)
+                .global(true)
+                .default_value("uniform")
+                .validator(is_weight),
+        )
+        .arg(
+            Arg::with_name("max-candidates")
+                .long("max-candidates")
+                .value_name("n")
+                .help("only pick from the first n quotes for a time, instead of all of them")
+                .takes_value(true)
+                .global(true)
+                .validator(is_max_candidates),
+        )
+        .arg(
+            Arg::with_name("direction")
+                .long("direction")
+                .value_name("forward|backward")
+                .help("which way to look for a quote when the time has none")
+                .takes_value(true)
+                .global(true)
+                .validator(is_direction)
+                .default_value("backward"),
+        )
+        .arg(
+            Arg::with_name("exact")
+                .long("exact")
+                .help("fail if the time has no quote, rather than using a nearby one")
+                .global(true)
+                .conflicts_with("direction"),
+        )
+        .arg(
+            Arg::with_name("nearest")
+                .long("nearest")
+                .help("use the closest time with a quote, in either direction")
+                .global(true)
+                .conflicts_with_all(&["direction", "exact"]),
+        )
+        .arg(
+            Arg::with_name("random")
+                .long("random")
+                .help("show any quote, ignoring the time")
+                .global(true)
+                .conflicts_with_all(&["at", "direction", "exact", "nearest"]),
+        )
+        .arg(
+            Arg::with_name("index")
+                .long("index")
+                .value_name("n")
+                .help("use the nth quote for the --at time, counting from 0")
+                .takes_value(true)
+                .global(true)
+                .requires("at")
+                .conflicts_with_all(&["direction", "exact", "nearest", "random"])
+                .validator(is_index),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .value_name("u64")
+                .help("seed for choosing between quotes for the same time")
+                .takes_value(true)
+                .global(true)
+                .validator(is_seed),
+        )
+        .arg(
+            Arg::with_name("daily")
+                .long("daily")
+                .help("pick the same quote for a minute for the whole day")
+                .global(true)
+                .conflicts_with("seed"),
+        )
+        .arg(
+            Arg::with_name("12h")
+                .long("12h")
+                .help("show times on a 12-hour clock")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("width")
+                .long("width")
+                .value_name("columns")
+                .help("wrap the quote at this width, instead of the terminal's width")
+                .takes_value(true)
+                .global(true)
+                .validator(is_width),
+        )
+        .arg(
+            Arg::with_name("fuzzy")
+                .long("fuzzy")
+                .value_name("n")
+                .help("highlight the closest phrase when the time phrase isn't in the quote, allowing this many typos, 0 turns this off")
+                .takes_value(true)
+                .default_value("2")
+                .global(true)
+                .validator(is_fuzzy),
+        )
+        .arg(
+            Arg::with_name("ascii")
+                .long("ascii")
+                .help("print only plain ASCII, for terminals or fonts without curly quotes, dashes or accents")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .short("v")
+                .long("verbose")
+                .multiple(true)
+                .help("print what's going on to stderr, use twice to also print each match")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("file")
+                .long("file")
+                .value_name("path")
+                .help("also load quotes from this CSV or JSON file, or - for stdin, can be repeated")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("file-format")
+                .long("file-format")
+                .value_name("format")
+                .help("how the --file quotes are stored, instead of going by the extension")
+                .takes_value(true)
+                .global(true)
+                .possible_values(&["csv", "json"])
+                .requires("file"),
+        )
+        .arg(
+            Arg::with_name("no-builtin")
+                .long("no-builtin")
+                .help("don't load the built-in quotes, only those from --file")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("author")
+                .long("author")
+                .value_name("text")
+                .help("only use quotes by authors containing this text")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("source")
+                .long("source")
+                .value_name("text")
+                .help("only use quotes from sources containing this text")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("exclude-author")
+                .long("exclude-author")
+                .value_name("text")
+                .help("don't use quotes by authors containing this text")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("exclude-source")
+                .long("exclude-source")
+                .value_name("text")
+                .help("don't use quotes from sources containing this text")
+                .takes_value(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("censor")
+                .long("censor")
+                .value_name("path")
+                .help("don't use quotes with strong language, from a built-in list or from --censor=words.txt with a word on each line")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("dedup-attribution")
+                .long("dedup-attribution")
+                .help("leave the author out of the source, or the source out of the author, when one repeats the other")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("mask")
+                .long("mask")
+                .help("with --censor, use every quote but replace the words with asterisks")
+                .requires("censor")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("min-length")
+                .long("min-length")
+                .value_name("chars")
+                .help("only use quotes with at least this many characters")
+                .takes_value(true)
+                .global(true)
+                .validator(is_length),
+        )
+        .arg(
+            Arg::with_name("max-length")
+                .long("max-length")
+                .value_name("chars")
+                .help("only use quotes with at most this many characters")
+                .takes_value(true)
+                .global(true)
+                .validator(is_length),
+        )
+        .arg(
+            Arg::with_name("no-cache")
+                .long("no-cache")
+                .help("always parse the --file, without caching it")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("refresh-cache")
+                .long("refresh-cache")
+                .help("parse the --file again and update the cache")
+                .global(true)
+                .conflicts_with("no-cache"),
+        )
+        .subcommand(
+            SubCommand::with_name("once")
+                .about("display a single quote and exit, which is the default")
+                .args(&render_args())
+                .args(&show_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("clock")
+                .about("keep displaying a quote each minute")
+                .args(&render_args())
+                .args(&show_args())
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .value_name("seconds")
+                        .help("the longest to wait between checking the time")
+                        .takes_value(true)
+                        .validator(is_interval),
+                )
+                .arg(
+                    Arg::with_name("jitter")
+                        .long("jitter")
+                        .value_name("seconds")
+                        .help("wait up to this much longer after each minute starts, so many clocks don't refresh at once")
+                        .takes_value(true)
+                        .validator(is_jitter),
+                )
+                .arg(
+                    Arg::with_name("until")
+                        .long("until")
+                        .value_name("HH:MM")
+                        .help("count down the minutes to this time, and stop when it's reached")
+                        .takes_value(true)
+                        .validator(is_timestamp),
+                )
+                .arg(
+                    Arg::with_name("clear")
+                        .long("clear")
+                        .help("clear the screen and center each new quote"),
+                )
+                .arg(
+                    Arg::with_name("stream")
+                        .long("stream")
+                        .help("print a quote once each time the minute changes, and nothing in between")
+                        .conflicts_with_all(&["clear", "interval"]),
+                )
+                .arg(
+                    Arg::with_name("group-by-author")
+                        .long("group-by-author")
+                        .help("show the --author's quotes in order of time, moving on to the next one each minute")
+                        .conflicts_with("every"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("search")
+                .about("find quotes mentioning a word, author or source")
+                .args(&render_args())
+                .arg(
+                    Arg::with_name("query")
+                        .help("text to look for, ignoring case")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("author-only")
+                        .long("author-only")
+                        .help("only search the authors")
+                        .conflicts_with("source-only"),
+                )
+                .arg(
+                    Arg::with_name("source-only")
+                        .long("source-only")
+                        .help("only search the sources"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("list the authors or sources with how many quotes each has, or each time with the start of its first quote")
+                .arg(
+                    Arg::with_name("what")
+                        .possible_values(&["authors", "sources", "times"])
+                        .required(true),
+                )
+                .arg(collate_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("print the quote in another format")
+                .arg(
+                    Arg::with_name("as")
+                        .possible_values(&["html", "markdown"])
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .help("print every quote in order of time, ignoring --at"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("dump-csv")
+                .about("print the built-in quotes as they're embedded, to start a CSV of your own from")
+                .args(&output_args()),
+        )
+        .subcommand(SubCommand::with_name("validate").about("check the quotes CSV for broken rows"))
+        .subcommand(
+            SubCommand::with_name("count")
+                .about("print how many quotes there are for the time")
+                .arg(
+                    Arg::with_name("total")
+                        .long("total")
+                        .help("print how many quotes there are in total")
+                        .conflicts_with("at"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("completions")
+                .about("print a completion script for the shell")
+                .arg(
+                    Arg::with_name("shell")
+                        .possible_values(&Shell::variants())
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("bookmark").about("save the quote for the time to the bookmarks"),
+        )
+        .subcommand(
+            SubCommand::with_name("bookmarks")
+                .about("print the bookmarked quotes")
+                .args(&render_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("range")
+                .about("print every quote from one time to another, wrapping past midnight")
+                .args(&render_args())
+                .arg(
+                    Arg::with_name("start")
+                        .value_name("HH:MM")
+                        .required(true)
+                        .validator(is_timestamp),
+                )
+                .arg(
+                    Arg::with_name("end")
+                        .value_name("HH:MM")
+                        .required(true)
+                        .validator(is_timestamp),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("stats")
+                .about("summarize the quotes")
+                .arg(collate_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("lint")
+                .about("list the quotes whose context cannot be highlighted")
+                .arg(
+                    Arg::with_name("strict")
+                        .long("strict")
+                        .help("exit with an error if any quote cannot be highlighted"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("duplicates")
+                .about("list the quotes found under more than one time"),
+        )
+        .subcommand(SubCommand::with_name("man").about("print a man page"))
+        .subcommand(
+            SubCommand::with_name("coverage")
+                .about("list the minutes that have no quote")
+                .arg(
+                    Arg::with_name("counts")
+                        .long("counts")
+                        .help("list how many quotes every minute has"),
+                ),
+        )
+}
+
+/// The flags for how the quotes look and where they're printed, for the
+/// commands that render them
+fn render_args() -> Vec<Arg<'static, 'static>> {
+    let mut args = vec![
+        Arg::with_name("theme")
+            .long("theme")
+            .value_name("name")
+            .help("a set of colors to start from, which the other color flags change")
+            .takes_value(true)
+            .possible_values(&ColorSet::THEMES)
+            .default_value("default"),
+        Arg::with_name("author-colors")
+            .long("author-colors")
+            .value_name("author=color;...")
+            .help("colors for the author and source of these authors, e.g. \"Charles Dickens=blue;Jane Austen=#ff8800\"")
+            .takes_value(true)
+            .validator(is_author_colors),
+        Arg::with_name("highlight")
+            .long("highlight")
+            .value_name("color|#RRGGBB")
+            .help("color for the time phrase in the quote")
+            .takes_value(true)
+            .validator(is_color),
+        Arg::with_name("inactive")
+            .long("inactive")
+            .value_name("color|#RRGGBB")
+            .help("color for the rest of the quote")
+            .takes_value(true)
+            .validator(is_color),
+        Arg::with_name("active")
+            .long("active")
+            .value_name("color|#RRGGBB")
+            .help("color for the author and source")
+            .takes_value(true)
+            .validator(is_color),
+        Arg::with_name("highlight-bg")
+            .long("highlight-bg")
+            .value_name("color|#RRGGBB")
+            .help("background color for the time phrase in the quote")
+            .takes_value(true)
+            .validator(is_color),
+        Arg::with_name("inactive-bg")
+            .long("inactive-bg")
+            .value_name("color|#RRGGBB")
+            .help("background color for the rest of the quote")
+            .takes_value(true)
+            .validator(is_color),
+        Arg::with_name("active-bg")
+            .long("active-bg")
+            .value_name("color|#RRGGBB")
+            .help("background color for the author and source")
+            .takes_value(true)
+            .validator(is_color),
+        Arg::with_name("border")
+            .long("border")
+            .value_name("style")
+            .help("draw a box around the quote")
+            .takes_value(true)
+            .possible_values(&["none", "single", "rounded", "double"])
+            .default_value("none"),
+        Arg::with_name("format")
+            .long("format")
+            .value_name("format")
+            .help("how the quote should be printed")
+            .takes_value(true)
+            .possible_values(&["text", "json", "pango"])
+            .default_value("text"),
+        Arg::with_name("color")
+            .long("color")
+            .value_name("when")
+            .help("when to use colors, overriding NO_COLOR")
+            .takes_value(true)
+            .possible_values(&["auto", "always", "never"]),
+        Arg::with_name("max-width-author")
+            .long("max-width-author")
+            .value_name("columns")
+            .help("wrap the author and source when they're wider than this")
+            .takes_value(true)
+            .validator(is_width),
+        Arg::with_name("margin")
+            .long("margin")
+            .value_name("n")
+            .help("leave this many columns empty on both sides of the quote")
+            .takes_value(true)
+            .validator(is_margin),
+        Arg::with_name("indent")
+            .long("indent")
+            .value_name("n")
+            .help("indent the first line of the quote by this many spaces, the rest by 2 more")
+            .takes_value(true)
+            .validator(is_indent),
+        Arg::with_name("attribution-align")
+            .long("attribution-align")
+            .value_name("left|right|center")
+            .help("how to align the author and source under the quote")
+            .takes_value(true)
+            .possible_values(&["left", "right", "center"])
+            .default_value("left"),
+        Arg::with_name("highlight-all")
+            .long("highlight-all")
+            .help("highlight every time the time phrase appears in the quote, not just the first"),
+        Arg::with_name("context-marker")
+            .long("context-marker")
+            .value_name("chars")
+            .help("mark the time phrase with these when there are no colors, e.g. «» or [] or ****")
+            .takes_value(true)
+            .validator(is_marker),
+        Arg::with_name("no-wrap")
+            .long("no-wrap")
+            .help("print the quote on a single line, without wrapping it"),
+        Arg::with_name("quote-only")
+            .long("quote-only")
+            .help("print just the quote, without the author and source"),
+        Arg::with_name("attribution-only")
+            .long("attribution-only")
+            .help("print just the time, author and source, without the quote")
+            .conflicts_with("quote-only"),
+    ];
+    args.extend(style_args());
+    args.extend(output_args());
+    args
+}
+
+fn output_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("output")
+            .long("output")
+            .value_name("path")
+            .help("write the quote to this file instead, without colors unless --color always")
+            .takes_value(true),
+        Arg::with_name("append")
+            .long("append")
+            .help("add to the end of the --output file, rather than replacing it")
+            .requires("output"),
+    ]
+}
+
+/// The flags for showing a quote as time goes by, with `once` and `clock`
+fn show_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("log")
+            .long("log")
+            .value_name("path")
+            .help("append the time, author and source of each quote shown to this file")
+            .takes_value(true),
+        Arg::with_name("typewriter")
+            .long("typewriter")
+            .value_name("cps")
+            .help("type the quote out at this many characters per second")
+            .takes_value(true)
+            .conflicts_with_all(&["attribution-only", "border", "no-wrap"])
+            .validator(is_typewriter),
+        Arg::with_name("every")
+            .long("every")
+            .value_name("seconds")
+            .help("keep going, showing the next of the minute's quotes this often")
+            .takes_value(true)
+            .conflicts_with_all(&["index", "random"])
+            .validator(is_interval),
+        Arg::with_name("show-time")
+            .long("show-time")
+            .help("print the time above the quote, and the quote's time if it's different"),
+        Arg::with_name("dry-run").long("dry-run").help(
+            "print the time asked for, the time found and the quote's author and source, then exit",
+        ),
+    ]
+}
+
+fn style_args() -> Vec<Arg<'static, 'static>> {
+    const STYLES: [(&str, &str); 6] = [
+        ("highlight-bold", "make the time phrase bold"),
+        ("highlight-italic", "make the time phrase italic"),
+        ("inactive-bold", "make the rest of the quote bold"),
+        ("inactive-italic", "make the rest of the quote italic"),
+        ("active-bold", "make the author and source bold"),
+        ("active-italic", "make the author and source italic"),
     ];
 
-    if COLORS.contains(&val.to_ascii_lowercase().as_str()) {
-        return Ok(());
+    STYLES
+        .iter()
+        .map(|&(name, help)| Arg::with_name(name).long(name).help(help))
+        .collect()
+}
+
+/// `--collate`, for the commands that list names
+fn collate_arg() -> Arg<'static, 'static> {
+    Arg::with_name("collate")
+        .long("collate")
+        .value_name("LOCALE")
+        .help("sort names with the same count as this locale would, so accented names sit beside the rest")
+        .takes_value(true)
+        .validator(is_locale)
+}
+
+/// Loads the quotes from a file, using the cache unless told otherwise
+fn load_file(
+    path: &Path,
+    format: Option<FileFormat>,
+    no_cache: bool,
+    refresh: bool,
+) -> Result<(Vec<Quote>, usize), Error> {
+    let format = format.unwrap_or_else(|| FileFormat::from_path(path));
+    if path == Path::new("-") {
+        // the whole of stdin is read first, it can't be cached without a file to key it on
+        let mut data = vec![];
+        std::io::stdin().read_to_end(&mut data)?;
+        return literal::load_quotes_counted_as(data.as_slice(), format);
+    }
+
+    let parse = || {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        literal::load_quotes_counted_as(file, format)
+    };
+    let cache = match QuoteCache::user() {
+        Some(cache) if !no_cache => cache,
+        _ => return parse(),
+    };
+
+    if !refresh {
+        if let Some(loaded) = cache.load(path, format) {
+            return Ok(loaded);
+        }
+    }
+
+    let (quotes, skipped) = parse()?;
+    // the cache is only an optimization, so failing to write it isn't fatal
+    let _ = cache.store(path, format, &quotes, skipped);
+    Ok((quotes, skipped))
+}
+
+/// Loads the config from the path, or from the user's config if there is one
+fn load_config(path: Option<&str>) -> Result<Config, Error> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => match Config::user_path().filter(|path| path.exists()) {
+            Some(path) => path,
+            None => return Ok(Config::default()),
+        },
+    };
+    let toml = std::fs::read_to_string(&path)
+        .map_err(|err| Error::File("read the config", path.display().to_string(), err))?;
+    Config::from_toml(&toml).map_err(|err| Error::Usage(format!("{}: {}", path.display(), err)))
+}
+
+/// The flag's value if it was given, otherwise the config's value for it, and
+/// otherwise the flag's default
+fn setting<'a>(
+    flags: Flags<'a>,
+    name: &str,
+    config: &'a Option<String>,
+    validator: fn(String) -> Result<(), String>,
+) -> Result<Option<&'a str>, Error> {
+    match config {
+        Some(value) if flags.occurrences_of(name) == 0 => match validator(value.clone()) {
+            Ok(()) => Ok(Some(value)),
+            Err(err) => Err(Error::Usage(format!(
+                "invalid {} in the config: {}",
+                name, err
+            ))),
+        },
+        _ => Ok(flags.value_of(name)),
+    }
+}
+
+fn user_bookmarks() -> Result<Bookmarks, Error> {
+    Bookmarks::user().ok_or_else(|| {
+        let err = "there's no data directory to keep the bookmarks in";
+        Error::Io(std::io::Error::new(std::io::ErrorKind::NotFound, err))
+    })
+}
+
+/// Reports each broken row in the CSV, returning how many problems were found
+fn validate(reader: impl std::io::Read) -> usize {
+    let mut problems = 0;
+    let mut report = |line: u64, msg: String| {
+        problems += 1;
+        println!("line {}: {}", line, msg);
+    };
+
+    let mut reader = literal::csv_builder().flexible(true).from_reader(reader);
+    let mut record = csv::StringRecord::new();
+    loop {
+        let line = reader.position().line();
+        match reader.read_record(&mut record) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(err) => {
+                report(line, format!("cannot be read: {}", err));
+                continue;
+            }
+        }
+
+        if record.len() != 5 {
+            // an unquoted field with a newline in it splits the row in two
+            let hint = if record.len() < 5 {
+                ", a field with a line break has to be in double quotes"
+            } else {
+                ""
+            };
+            report(
+                line,
+                format!("expected 5 columns, found {}{}", record.len(), hint),
+            );
+            continue;
+        }
+
+        let time = &record[0];
+        if is_timestamp(time.to_string()).is_err() {
+            let numeric = time
+                .split(':')
+                .all(|d| d.len() == 2 && d.bytes().all(|b| b.is_ascii_digit()));
+            if numeric && time.split(':').count() == 2 {
+                report(line, format!("time '{}' is outside of 00:00..23:59", time));
+            } else {
+                report(line, format!("time '{}' is not HH:MM", time));
+            }
+        }
+
+        match record.deserialize::<Quote>(None) {
+            Ok(ref quote) if !quote.is_highlightable() => report(
+                line,
+                format!("context '{}' does not appear in the quote", quote.context()),
+            ),
+            Ok(..) => {}
+            Err(err) => report(line, format!("cannot be read: {}", err)),
+        }
+    }
+    problems
+}
+
+/// How a quote is chosen for a time
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Mode {
+    Around(Direction),
+    Exact,
+    Index(usize),
+    Nearest,
+    Random,
+}
+
+impl Mode {
+    fn select<'a>(self, db: &Database<'a>, time: TimeKey, rng: &mut impl Rng) -> Option<&'a Quote> {
+        match self {
+            Mode::Around(direction) => db.around_time(time, direction, rng),
+            Mode::Exact => db.at_time(time, rng),
+            Mode::Index(n) => db.at_time_index(time, n),
+            Mode::Nearest => db.nearest(time, rng),
+            Mode::Random => db.random(rng),
+        }
+    }
+
+    /// How many minutes were walked from the time to find the matched time
+    fn walked(self, time: TimeKey, matched: TimeKey) -> Option<usize> {
+        let (forward, backward) = (time.minutes_until(matched), matched.minutes_until(time));
+        match self {
+            Mode::Around(Direction::Forward) => Some(forward),
+            Mode::Around(Direction::Backward) => Some(backward),
+            Mode::Nearest => Some(forward.min(backward)),
+            Mode::Exact | Mode::Index(..) => Some(0),
+            Mode::Random => None,
+        }
+    }
+}
+
+/// Selects a quote for the time, failing if `--exact` was used and there's no quote for it
+fn pick<'a>(
+    db: &Database<'a>,
+    mode: Mode,
+    time: TimeKey,
+    rng: &mut impl Rng,
+    twelve_hour: bool,
+) -> Result<&'a Quote, Error> {
+    match mode.select(db, time, rng) {
+        Some(quote) => Ok(quote),
+        None if mode == Mode::Exact => Err(Error::NotFound(format!(
+            "no quote for {}",
+            time.format(twelve_hour)
+        ))),
+        None if matches!(mode, Mode::Index(..)) => Err(Error::NotFound(format!(
+            "no such quote for {}, it has {}",
+            time.format(twelve_hour),
+            db.count_at(time)
+        ))),
+        None => Err(Error::NoQuotes),
+    }
+}
+
+/// The timezone the current time is shown in
+#[derive(Debug, Copy, Clone)]
+enum Timezone {
+    Local,
+    Fixed(FixedOffset),
+    Named(Tz),
+}
+
+/// Where the current time comes from, and how long it takes to pass
+trait Clock {
+    fn now(&self) -> NaiveDateTime;
+
+    /// Waits for the delay, returning early with `true` if ctrl-c was pressed
+    fn sleep(&self, delay: Duration) -> bool {
+        sleep_until_interrupted(delay)
+    }
+}
+
+impl Clock for Timezone {
+    fn now(&self) -> NaiveDateTime {
+        self.convert(Utc::now())
+    }
+}
+
+/// A clock that starts at a made up time, and keeps time from there
+struct Pretend {
+    start: NaiveDateTime,
+    started: std::time::Instant,
+}
+
+impl Pretend {
+    fn new(start: NaiveDateTime) -> Self {
+        Self {
+            start,
+            started: std::time::Instant::now(),
+        }
+    }
+}
+
+impl Clock for Pretend {
+    fn now(&self) -> NaiveDateTime {
+        let elapsed = chrono::Duration::from_std(self.started.elapsed())
+            .unwrap_or_else(|_| chrono::Duration::zero());
+        self.start + elapsed
+    }
+}
+
+impl Timezone {
+    fn convert(self, utc: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            Timezone::Local => utc.with_timezone(&Local).naive_local(),
+            Timezone::Fixed(offset) => utc.with_timezone(&offset).naive_local(),
+            Timezone::Named(tz) => utc.with_timezone(&tz).naive_local(),
+        }
+    }
+}
+
+impl std::str::FromStr for Timezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sign, offset) = match (s.strip_prefix('+'), s.strip_prefix('-')) {
+            (Some(offset), _) => (1, offset),
+            (_, Some(offset)) => (-1, offset),
+            _ => {
+                return s
+                    .parse()
+                    .map(Timezone::Named)
+                    .map_err(|_| format!("Unknown timezone '{}'", s))
+            }
+        };
+
+        let mut parts = offset.split(':').map(|d| d.parse::<i32>().ok());
+        let offset = match (parts.next(), parts.next().unwrap_or(Some(0)), parts.next()) {
+            (Some(Some(hh @ 0..=23)), Some(mm @ 0..=59), None) => {
+                FixedOffset::east_opt(sign * (hh * 60 + mm) * 60)
+            }
+            _ => None,
+        };
+        offset
+            .map(Timezone::Fixed)
+            .ok_or_else(|| String::from("A timezone offset must be in the form +HH:MM"))
+    }
+}
+
+/// The count and the noun, which is plural unless there's just one
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        n => format!("{} {}s", n, noun),
+    }
+}
+
+/// Splits the marker in half, into what goes before the context and what goes after it.
+///
+/// A marker that can't be split evenly goes on both sides
+fn split_marker(marker: &str) -> (String, String) {
+    let chars = marker.chars().collect::<Vec<_>>();
+    if chars.len() % 2 != 0 {
+        return (marker.to_string(), marker.to_string());
+    }
+    let (open, close) = chars.split_at(chars.len() / 2);
+    (open.iter().collect(), close.iter().collect())
+}
+
+/// Writes the time being shown, along with the quote's time when it's for a nearby minute
+fn write_time(
+    buffer: &mut Buffer,
+    time: TimeKey,
+    quote: &Quote,
+    colors: &ColorSet,
+    twelve_hour: bool,
+) -> std::io::Result<()> {
+    let matched = TimeKey::try_from(quote.time()).expect("indexed time");
+
+    buffer.set_color(&colors.active)?;
+    write!(buffer, "{}", time.format(twelve_hour))?;
+    if matched != time {
+        write!(buffer, " (showing {})", matched.format(twelve_hour))?;
+    }
+    buffer.reset()?;
+    writeln!(buffer)
+}
+
+fn write_remaining(
+    buffer: &mut Buffer,
+    remaining: usize,
+    colors: &ColorSet,
+) -> std::io::Result<()> {
+    buffer.set_color(&colors.active)?;
+    write!(buffer, "{} remaining", plural(remaining, "minute"))?;
+    buffer.reset()?;
+    writeln!(buffer)
+}
+
+/// Appends a line about the quote to the log, as JSON if `--format json` was used
+fn write_log(
+    log: &mut std::fs::File,
+    now: NaiveDateTime,
+    quote: &Quote,
+    json: bool,
+) -> std::io::Result<()> {
+    let shown = now.format("%Y-%m-%dT%H:%M:%S").to_string();
+    let (author, source) = (quote.author().trim(), quote.source().trim());
+    if json {
+        let entry = serde_json::json!({
+            "shown": shown,
+            "time": quote.time(),
+            "author": author,
+            "source": source,
+        });
+        writeln!(log, "{}", entry)?;
+    } else {
+        writeln!(log, "{} {} {} – {}", shown, quote.time(), author, source)?;
+    }
+    log.flush()
+}
+
+/// Clears the screen and moves the cursor to the top left
+// the cursor stays hidden while the screen is being cleared, until
+// `restore_terminal` shows it again
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H\x1b[?25l";
+
+/// Types out the quote a character at a time, then prints its author and source.
+///
+/// Returns `true` if it was interrupted partway through
+fn typewrite(
+    out: &mut dyn Write,
+    renderer: &Renderer,
+    quote: &Quote,
+    cps: u32,
+) -> std::io::Result<bool> {
+    let delay = Duration::from_secs(1) / cps;
+    let colors = renderer.colors_for(quote);
+
+    let mut buffer = renderer.buffer();
+    writeln!(buffer)?;
+    emit(out, &buffer)?;
+
+    for (ch, highlight) in quote.wrapped_chars(&renderer.layout) {
+        let mut buffer = renderer.buffer();
+        if highlight {
+            buffer.set_color(&colors.highlight)?;
+        } else {
+            buffer.set_color(&colors.inactive)?;
+        }
+        write!(buffer, "{}", ch)?;
+        buffer.reset()?;
+        emit(out, &buffer)?;
+
+        if !ch.is_whitespace() && sleep_until_interrupted(delay) {
+            return Ok(true);
+        }
+    }
+
+    let mut buffer = renderer.buffer();
+    writeln!(buffer)?;
+    if !renderer.quote_only {
+        writeln!(buffer)?;
+        let mut byline = renderer.buffer();
+        quote.format_byline(&mut byline, &colors, &renderer.layout)?;
+        if renderer.ascii {
+            let byline = literal::to_ascii(&String::from_utf8_lossy(byline.as_slice()));
+            write!(buffer, "{}", byline)?;
+        } else {
+            buffer.write_all(byline.as_slice())?;
+        }
+    }
+    emit(out, &buffer)?;
+    Ok(false)
+}
+
+/// Writes out what's in the buffer straight away
+fn emit(out: &mut dyn Write, buffer: &Buffer) -> std::io::Result<()> {
+    out.write_all(buffer.as_slice())?;
+    out.flush()
+}
+
+/// Clears the screen, then writes the rendered quote centered vertically
+fn clear_screen(buffer: &mut Buffer, rendered: &[u8]) -> std::io::Result<()> {
+    write!(buffer, "{}", CLEAR_SCREEN)?;
+
+    if let Some((_, Height(height))) = terminal_size::terminal_size() {
+        let lines = rendered.iter().filter(|&&b| b == b'\n').count();
+        let padding = usize::from(height).saturating_sub(lines) / 2;
+        write!(buffer, "{}", "\n".repeat(padding))?;
+    }
+    buffer.write_all(rendered)
+}
+
+/// Uses the requested width, or the terminal's width minus a small margin,
+/// falling back to 60 columns when there's no terminal
+fn resolve_width(requested: Option<usize>, terminal: Option<usize>) -> usize {
+    const MARGIN: usize = 4;
+    const FALLBACK: usize = 60;
+
+    requested
+        .or_else(|| terminal.map(|w| w.saturating_sub(MARGIN)))
+        .filter(|&w| w > 0)
+        .unwrap_or(FALLBACK)
+}
+
+fn time_of(now: &NaiveDateTime) -> TimeKey {
+    TimeKey::new(now.hour() as u8, now.minute() as u8).expect("valid time")
+}
+
+/// Adds up to `jitter` to the delay, so many clocks don't all wake up at once.
+///
+/// It never wakes up past the minute after `next_minute`, so no minute is skipped
+fn jittered(
+    delay: Duration,
+    next_minute: Duration,
+    jitter: Duration,
+    rng: &mut impl Rng,
+) -> Duration {
+    let millis = u64::try_from(jitter.as_millis()).unwrap_or(u64::MAX);
+    let offset = Duration::from_millis(rng.gen_range(0, millis.saturating_add(1)));
+    (delay + offset).min(next_minute + Duration::from_millis(59_999))
+}
+
+fn until_next_minute(second: u32, nanos: u32) -> Duration {
+    // chrono represents a leap second with nanos past 1_000_000_000
+    let elapsed = Duration::new(u64::from(second.min(59)), nanos.min(999_999_999));
+    Duration::from_secs(60) - elapsed
+}
+
+fn coverage(db: &Database, counts: bool, twelve_hour: bool) {
+    let mut uncovered = 0;
+    for hh in 0..24 {
+        for mm in 0..60 {
+            let time = TimeKey::new(hh, mm).expect("valid time");
+            let count = db.count_at(time);
+            if count == 0 {
+                uncovered += 1;
+            }
+            let time = time.format(twelve_hour);
+            if counts {
+                println!("{} {}", time, count);
+            } else if count == 0 {
+                println!("{}", time);
+            }
+        }
+    }
+    println!("{} of {} minutes uncovered", uncovered, 24 * 60);
+}
+
+/// Writes a roff man page, built from the flags and subcommands that `app` declares
+fn man_page(out: &mut impl Write) -> std::io::Result<()> {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('-', "\\-");
+    let app = app();
+    let name = env!("CARGO_PKG_NAME");
+    writeln!(
+        out,
+        ".TH {} 1 \"\" \"{} {}\"",
+        name.to_uppercase(),
+        name,
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(out, ".SH NAME\n{} \\- {}", name, escape(ABOUT))?;
+    writeln!(out, ".SH SYNOPSIS\n.B {}\n[OPTIONS] [COMMAND]", name)?;
+
+    writeln!(out, ".SH OPTIONS")?;
+    for (term, help) in man_entries(&app) {
+        writeln!(out, ".TP\n.B {}\n{}", escape(&term), escape(help))?;
+    }
+
+    writeln!(out, ".SH COMMANDS")?;
+    for command in &app.p.subcommands {
+        let about = command.p.meta.about.unwrap_or_default();
+        writeln!(
+            out,
+            ".TP\n.B {}\n{}",
+            escape(command.get_name()),
+            escape(about)
+        )?;
+    }
+
+    // the top-level flags were already listed, so only each subcommand's own are added
+    writeln!(out, ".SH COMMAND OPTIONS")?;
+    for command in &app.p.subcommands {
+        let entries = man_entries(command);
+        if entries.is_empty() {
+            continue;
+        }
+        writeln!(out, ".SS {}", escape(command.get_name()))?;
+        for (term, help) in entries {
+            writeln!(out, ".TP\n.B {}\n{}", escape(&term), escape(help))?;
+        }
+    }
+
+    if let Some(exit_status) = app.p.meta.more_help {
+        // the help has it under its own heading, indented
+        writeln!(out, ".SH EXIT STATUS")?;
+        let lines = exit_status.lines().skip(1);
+        for (code, meaning) in lines.filter_map(|l| l.trim().split_once("    ")) {
+            writeln!(out, ".TP\n.B {}\n{}", code, escape(meaning.trim()))?;
+        }
+    }
+    Ok(())
+}
+
+/// The positional arguments, then the flags, of a command with their help,
+/// leaving out the hidden ones
+fn man_entries<'a>(app: &'a App) -> Vec<(String, &'a str)> {
+    let value = |names: Option<Vec<&str>>, name: &str| match names {
+        Some(names) => names
+            .iter()
+            .map(|n| format!("<{}>", n))
+            .collect::<Vec<_>>()
+            .join(" "),
+        None => format!("<{}>", name),
+    };
+    let switch = |short: Option<char>, long: Option<&str>| {
+        let short = short.map(|c| format!("-{}", c));
+        let long = long.map(|l| format!("--{}", l));
+        short.into_iter().chain(long).collect::<Vec<_>>().join(", ")
+    };
+
+    let mut entries = vec![];
+    for arg in app.p.positionals.values() {
+        if !arg.b.is_set(ArgSettings::Hidden) {
+            let names = arg
+                .v
+                .val_names
+                .as_ref()
+                .map(|n| n.values().copied().collect());
+            entries.push((value(names, arg.b.name), arg.b.help.unwrap_or_default()));
+        }
+    }
+
+    let mut flags = vec![];
+    for arg in &app.p.flags {
+        if !arg.b.is_set(ArgSettings::Hidden) {
+            flags.push((
+                switch(arg.s.short, arg.s.long),
+                arg.b.help.unwrap_or_default(),
+            ));
+        }
+    }
+    for arg in &app.p.opts {
+        if !arg.b.is_set(ArgSettings::Hidden) {
+            let names = arg
+                .v
+                .val_names
+                .as_ref()
+                .map(|n| n.values().copied().collect());
+            let term = format!(
+                "{} {}",
+                switch(arg.s.short, arg.s.long),
+                value(names, arg.b.name)
+            );
+            flags.push((term, arg.b.help.unwrap_or_default()));
+        }
+    }
+    // clap lists them by name too
+    flags.sort_by(|(a, _), (b, _)| a.trim_start_matches('-').cmp(b.trim_start_matches('-')));
+    entries.extend(flags);
+    entries
+}
+
+fn stats(db: &Database, matches: &ArgMatches, twelve_hour: bool) {
+    let mut lengths = db.quotes().map(Quote::len).collect::<Vec<_>>();
+    lengths.sort();
+    let average = lengths.iter().sum::<usize>() / lengths.len().max(1);
+    let median = lengths.get(lengths.len() / 2).copied().unwrap_or(0);
+
+    let mut covered = 0;
+    let mut busiest = None;
+    for hh in 0..24 {
+        for mm in 0..60 {
+            let time = TimeKey::new(hh, mm).expect("valid time");
+            let count = db.count_at(time);
+            if count > 0 {
+                covered += 1;
+            }
+            // the earliest minute wins a tie
+            if busiest.is_none_or(|(_, most)| count > most) {
+                busiest = Some((time, count));
+            }
+        }
+    }
+
+    let (mut authors, mut sources) = (db.authors(), db.sources());
+    if let Some(collation) = collation(matches) {
+        collation.sort(&mut authors);
+        collation.sort(&mut sources);
+    }
+
+    println!("{:>18} {}", "quotes", db.len());
+    println!("{:>18} {}", "authors", authors.len());
+    println!("{:>18} {}", "sources", sources.len());
+    println!("{:>18} {} of {}", "minutes covered", covered, 24 * 60);
+    println!("{:>18} {}", "minutes uncovered", 24 * 60 - covered);
+    println!("{:>18} {} characters", "average length", average);
+    println!("{:>18} {} characters", "median length", median);
+    if let Some((time, count)) = busiest {
+        println!(
+            "{:>18} {} with {}",
+            "busiest minute",
+            time.format(twelve_hour),
+            plural(count, "quote")
+        );
+    }
+    // a tie goes to whichever comes first by name
+    for (label, counts) in &[("top author", &authors), ("top source", &sources)] {
+        if let Some((name, count)) = counts.first() {
+            println!("{:>18} {} with {}", label, name, plural(*count, "quote"));
+        }
+    }
+}
+
+/// The `--collate` of `list` or `stats`
+fn collation(matches: &ArgMatches) -> Option<Collation> {
+    matches
+        .value_of("collate")
+        .map(|s| s.parse().expect("validated locale"))
+}
+
+fn color_choice(flag: Option<&str>) -> ColorChoice {
+    match flag {
+        Some("always") => ColorChoice::Always,
+        Some("never") => ColorChoice::Never,
+        Some(_) => ColorChoice::Auto,
+        // https://no-color.org/
+        None => match std::env::var_os("NO_COLOR") {
+            Some(ref s) if !s.is_empty() => ColorChoice::Never,
+            _ => ColorChoice::Auto,
+        },
+    }
+}
+
+fn is_timestamp(val: String) -> Result<(), String> {
+    parse_timestamp(&val).map(|_| ())
+}
+
+fn parse_timestamp(val: &str) -> Result<TimeKey, String> {
+    TimeKey::try_from(val)
+        .map_err(|_| String::from("The value must be a valid 24-hour timestamp, HH:MM"))
+}
+
+fn is_direction(val: String) -> Result<(), String> {
+    val.parse::<Direction>().map(|_| ())
+}
+
+fn is_locale(val: String) -> Result<(), String> {
+    val.parse::<Collation>().map(|_| ())
+}
+
+fn is_weight(val: String) -> Result<(), String> {
+    val.parse::<Weight>().map(|_| ())
+}
+
+fn is_width(val: String) -> Result<(), String> {
+    match val.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(()),
+        _ => Err(String::from("The width must be a positive number")),
+    }
+}
+
+fn is_max_candidates(val: String) -> Result<(), String> {
+    match val.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(()),
+        _ => Err(String::from(
+            "The number of candidates must be a positive number",
+        )),
+    }
+}
+
+fn is_indent(val: String) -> Result<(), String> {
+    val.parse::<usize>()
+        .map(|_| ())
+        .map_err(|_| String::from("The indent must be a number"))
+}
+
+fn is_margin(val: String) -> Result<(), String> {
+    val.parse::<usize>()
+        .map(|_| ())
+        .map_err(|_| String::from("The margin must be a number"))
+}
+
+fn is_fuzzy(val: String) -> Result<(), String> {
+    val.parse::<usize>()
+        .map(|_| ())
+        .map_err(|_| String::from("The number of typos must be a number"))
+}
+
+fn is_typewriter(val: String) -> Result<(), String> {
+    match val.parse::<u32>() {
+        Ok(n) if n > 0 => Ok(()),
+        _ => Err(String::from(
+            "The speed must be a positive number of characters per second",
+        )),
+    }
+}
+
+fn is_length(val: String) -> Result<(), String> {
+    val.parse::<usize>()
+        .map(|_| ())
+        .map_err(|_| String::from("The length must be a number of characters"))
+}
+
+fn is_marker(val: String) -> Result<(), String> {
+    if val.is_empty() {
+        return Err(String::from("The marker can't be empty"));
+    }
+    Ok(())
+}
+
+fn is_jitter(val: String) -> Result<(), String> {
+    match val.parse::<u64>() {
+        Ok(n) if n < 60 => Ok(()),
+        _ => Err(String::from(
+            "The jitter must be a number of seconds less than 60",
+        )),
+    }
+}
+
+fn is_interval(val: String) -> Result<(), String> {
+    match val.parse::<u64>() {
+        Ok(n) if n > 0 => Ok(()),
+        _ => Err(String::from(
+            "The interval must be a positive number of seconds",
+        )),
+    }
+}
+
+fn is_now(val: String) -> Result<(), String> {
+    parse_now(&val).map(|_| ())
+}
+
+fn parse_now(val: &str) -> Result<NaiveDateTime, String> {
+    NaiveDateTime::parse_from_str(val, "%Y-%m-%d %H:%M")
+        .map_err(|_| String::from("The value must be a date and time, YYYY-MM-DD HH:MM"))
+}
+
+fn is_author_colors(val: String) -> Result<(), String> {
+    parse_author_colors(&val).map(|_| ())
+}
+
+/// Parses `author=color` pairs separated by `;`, keyed by the lowercased author
+fn parse_author_colors(val: &str) -> Result<HashMap<String, Color>, String> {
+    val.split(';')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((author, color)) => Ok((author.trim().to_lowercase(), parse_color(color)?)),
+            None => Err(format!(
+                "'{}' should be in the form author=color",
+                pair.trim()
+            )),
+        })
+        .collect()
+}
+
+fn is_theme(val: String) -> Result<(), String> {
+    match ColorSet::theme(&val) {
+        Some(..) => Ok(()),
+        None => Err(format!(
+            "Unknown theme, available: {}",
+            ColorSet::THEMES.join(", ")
+        )),
+    }
+}
+
+fn is_timezone(val: String) -> Result<(), String> {
+    val.parse::<Timezone>().map(|_| ())
+}
+
+fn is_index(val: String) -> Result<(), String> {
+    val.parse::<usize>()
+        .map(|_| ())
+        .map_err(|_| String::from("The index must be a number"))
+}
+
+fn is_seed(val: String) -> Result<(), String> {
+    val.parse::<u64>()
+        .map(|_| ())
+        .map_err(|_| String::from("The seed must be an unsigned 64-bit integer"))
+}
+
+fn is_color(val: String) -> Result<(), String> {
+    parse_color(&val).map(|_| ())
+}
+
+/// Parses a named color, a `#RRGGBB` hex color or an `rgb(r,g,b)` color.
+///
+/// 24-bit colors are written as is, terminals without truecolor support will
+/// approximate them (or ignore them) on their own
+fn parse_color(val: &str) -> Result<Color, String> {
+    const COLORS: [&str; 9] = [
+        "black", "blue", "green", "red", "cyan", "magenta", "yellow", "white", "grey",
+    ];
+
+    let val = val.trim().to_ascii_lowercase();
+    let color = match val.as_str() {
+        "black" => Color::Black,
+        "blue" => Color::Blue,
+        "green" => Color::Green,
+        "red" => Color::Red,
+        "cyan" => Color::Cyan,
+        "magenta" => Color::Magenta,
+        "yellow" => Color::Yellow,
+        "white" => Color::White,
+        "grey" => Color::Ansi256(8),
+        s if s.starts_with('#') => parse_hex(&s[1..])?,
+        s if s.starts_with("rgb(") && s.ends_with(')') => parse_rgb(&s[4..s.len() - 1])?,
+        _ => {
+            return Err(format!(
+                "Unknown color, available: {}, #RRGGBB or rgb(r,g,b)",
+                COLORS.join(", ")
+            ))
+        }
+    };
+    Ok(color)
+}
+
+fn parse_hex(hex: &str) -> Result<Color, String> {
+    let err = || String::from("A hex color must be in the form #RRGGBB");
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(err());
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| err());
+    Ok(Color::Rgb(channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn parse_rgb(rgb: &str) -> Result<Color, String> {
+    let err =
+        || String::from("An rgb color must be in the form rgb(r,g,b), each between 0 and 255");
+
+    let mut channels = rgb
+        .split(',')
+        .map(|c| c.trim().parse::<u8>().map_err(|_| err()));
+    match (
+        channels.next(),
+        channels.next(),
+        channels.next(),
+        channels.next(),
+    ) {
+        (Some(r), Some(g), Some(b), None) => Ok(Color::Rgb(r?, g?, b?)),
+        _ => Err(err()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wakes_at_the_start_of_the_next_minute() {
+        assert_eq!(until_next_minute(0, 0), Duration::from_secs(60));
+        assert_eq!(until_next_minute(30, 0), Duration::from_secs(30));
+        assert_eq!(until_next_minute(59, 0), Duration::from_secs(1));
+        assert_eq!(
+            until_next_minute(59, 500_000_000),
+            Duration::from_millis(500)
+        );
+        // a leap second
+        assert!(until_next_minute(59, 1_500_000_000) > Duration::from_secs(0));
+    }
+
+    #[test]
+    fn parse_truecolor() {
+        assert_eq!(parse_color("#ff8800"), Ok(Color::Rgb(255, 136, 0)));
+        assert_eq!(parse_color("#FF8800"), Ok(Color::Rgb(255, 136, 0)));
+        assert_eq!(parse_color("rgb(255,136,0)"), Ok(Color::Rgb(255, 136, 0)));
+        assert_eq!(parse_color("rgb(255, 136, 0)"), Ok(Color::Rgb(255, 136, 0)));
+        assert_eq!(parse_color("blue"), Ok(Color::Blue));
+
+        assert!(parse_color("rgb(300,0,0)").is_err());
+        assert!(parse_color("rgb(1,2)").is_err());
+        assert!(parse_color("#ggg").is_err());
+        assert!(parse_color("#gggggg").is_err());
+        assert!(parse_color("mauve").is_err());
+    }
+
+    #[test]
+    fn resolve_the_width() {
+        // without a terminal
+        assert_eq!(resolve_width(None, None), 60);
+        assert_eq!(resolve_width(Some(40), None), 40);
+        // the terminal's width is kept off of the edge
+        assert_eq!(resolve_width(None, Some(100)), 96);
+        assert_eq!(resolve_width(Some(40), Some(100)), 40);
+        // a terminal too narrow to measure
+        assert_eq!(resolve_width(None, Some(2)), 60);
+    }
+
+    /// With `clock` and nothing else
+    fn clock() -> ShowOptions<'static> {
+        ShowOptions {
+            clock: true,
+            once: false,
+            every: None,
+            interval: Duration::from_secs(60),
+            jitter: Duration::from_secs(0),
+            until: None,
+            clear: false,
+            streaming: false,
+            reading: false,
+            show_time: false,
+            dry_run: false,
+            typewriter: None,
+            log: None,
+        }
+    }
+
+    fn at(hh: u32, mm: u32, ss: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, 1)
+            .and_then(|date| date.and_hms_opt(hh, mm, ss))
+            .unwrap()
+    }
+
+    #[test]
+    fn the_interval_caps_the_wait() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let show = ShowOptions {
+            interval: Duration::from_secs(10),
+            ..clock()
+        };
+        assert_eq!(show.delay(&at(12, 0, 0), &mut rng), Duration::from_secs(10));
+        // but it still wakes up for the next minute
+        assert_eq!(show.delay(&at(12, 0, 55), &mut rng), Duration::from_secs(5));
+        assert_eq!(
+            clock().delay(&at(12, 0, 20), &mut rng),
+            Duration::from_secs(40)
+        );
+
+        // --every sleeps for as long as it's asked to, when it isn't a clock
+        let every = ShowOptions {
+            clock: false,
+            every: Some(Duration::from_secs(90)),
+            ..clock()
+        };
+        assert_eq!(
+            every.delay(&at(12, 0, 30), &mut rng),
+            Duration::from_secs(90)
+        );
+    }
+
+    #[test]
+    fn jitter_stays_within_the_next_minute() {
+        let show = ShowOptions {
+            jitter: Duration::from_secs(30),
+            ..clock()
+        };
+        for seed in 0..100 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            for &second in &[0, 20, 45, 59] {
+                let base = clock().delay(&at(12, 0, second), &mut rng);
+                let delay = show.delay(&at(12, 0, second), &mut rng);
+                assert!(delay >= base, "{:?} < {:?}", delay, base);
+                assert!(delay <= base + show.jitter);
+                // it wakes up during the next minute, so that minute is still shown
+                assert!(delay < base + Duration::from_secs(60));
+            }
+        }
+
+        // the same seed sleeps the same
+        let delay = |seed| show.delay(&at(12, 0, 0), &mut StdRng::seed_from_u64(seed));
+        assert_eq!(delay(7), delay(7));
+
+        // even the most it could add is cut off at the end of the next minute
+        let most = jittered(
+            Duration::from_secs(1),
+            Duration::from_secs(1),
+            Duration::from_secs(3600),
+            &mut StdRng::seed_from_u64(0),
+        );
+        assert!(most < Duration::from_secs(61));
+    }
+
+    /// A clock that's at each of the times in turn, moving on to the next one
+    /// whenever it's slept on, and then stopping as if ctrl-c was pressed
+    struct Ticks(std::cell::RefCell<std::collections::VecDeque<NaiveDateTime>>);
+
+    impl Clock for Ticks {
+        fn now(&self) -> NaiveDateTime {
+            *self.0.borrow().front().expect("another tick")
+        }
+
+        fn sleep(&self, _: Duration) -> bool {
+            let mut ticks = self.0.borrow_mut();
+            ticks.pop_front();
+            ticks.is_empty()
+        }
+    }
+
+    /// What the clock prints, with the flags after `literal`, over the ticks
+    fn run_clock(args: &[&str], ticks: &[NaiveDateTime]) -> String {
+        let quotes = literal::load_quotes(
+            concat!(
+                "12:00|noon|Noon, and all was quiet.|Noon Book|Author\n",
+                "12:00|midday|At midday the bells rang.|Bell Book|Author\n",
+                "12:01|a minute past noon|A minute past noon.|Minute Book|Author\n",
+                "12:02|two past noon|It was two past noon.|Late Book|Author\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+        let mut db = Database::new(&quotes);
+
+        let matches =
+            app().get_matches_from(std::iter::once("literal").chain(args.iter().copied()));
+        let (name, sub) = matches.subcommand();
+        let flags = Flags { top: &matches, sub };
+        let config = Config::default();
+        let mut options = Options::new(flags, &config).unwrap();
+        options.time_source = Box::new(Ticks(std::cell::RefCell::new(
+            ticks.iter().copied().collect(),
+        )));
+        let renderer = Renderer::new(flags, &config, &options).unwrap();
+        let show = ShowOptions::new(flags, name, sub).unwrap();
+
+        let mut out = vec![];
+        keep_showing(&mut db, &mut options, &renderer, &show, None, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn a_stream_prints_once_for_each_minute() {
+        let ticks = [
+            at(12, 0, 0),
+            at(12, 0, 20),
+            at(12, 0, 40),
+            at(12, 0, 59),
+            at(12, 1, 0),
+            at(12, 1, 30),
+            at(12, 2, 0),
+            at(12, 2, 1),
+        ];
+        let args = ["--color", "never", "--format", "json", "clock", "--stream"];
+        let out = run_clock(&args, &ticks);
+        let times = out
+            .lines()
+            .map(|line| {
+                serde_json::from_str::<Quote>(line)
+                    .unwrap()
+                    .time()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(times, ["12:00", "12:01", "12:02"]);
+
+        // while the clock leaves the quote it's showing alone until it changes
+        let args = ["--color", "never", "--no-wrap", "clock"];
+        let out = run_clock(&args, &ticks[..4]);
+        assert_eq!(out.matches("Author").count(), 1);
+    }
+
+    #[test]
+    fn reading_turns_through_the_authors_quotes_in_order() {
+        let quotes = [
+            ("23:50", "At ten to midnight."),
+            ("00:05", "At five past midnight."),
+            ("12:30", "At half past twelve."),
+        ]
+        .iter()
+        .map(|&(time, text)| Quote::new(time, "", text, "Source", "Ann Author").unwrap())
+        .collect::<Vec<_>>();
+        let db = Database::new(&quotes);
+        let pages = pages(&db);
+        let time = |s| TimeKey::try_from(s).unwrap();
+        assert_eq!(
+            pages.iter().map(|&(at, _)| at).collect::<Vec<_>>(),
+            [time("00:05"), time("12:30"), time("23:50")]
+        );
+
+        // from the first quote after the time, a page a minute, back to the start after the last
+        let mut page = None;
+        let mut read = vec![];
+        for minute in 0..5 {
+            let now = TimeKey::new(12, minute).unwrap();
+            page = Some(turn_page(&pages, page, now));
+            read.push(pages[page.unwrap()].1.quote());
+        }
+        assert_eq!(
+            read,
+            [
+                "At half past twelve.",
+                "At ten to midnight.",
+                "At five past midnight.",
+                "At half past twelve.",
+                "At ten to midnight.",
+            ]
+        );
+
+        // there's nothing after the last one, so it starts from the first
+        assert_eq!(turn_page(&pages, None, time("23:55")), 0);
+    }
+
+    #[test]
+    fn convert_to_a_timezone() {
+        let utc = Utc.from_utc_datetime(&at(10, 15, 0));
+        let time = |zone: &str| {
+            let now = zone.parse::<Timezone>().unwrap().convert(utc);
+            (now.hour(), now.minute())
+        };
+        assert_eq!(time("+02:00"), (12, 15));
+        assert_eq!(time("-03:30"), (6, 45));
+        assert_eq!(time("+05"), (15, 15));
+        assert_eq!(time("Asia/Kolkata"), (15, 45));
+        assert_eq!(time("America/New_York"), (5, 15));
+
+        assert!("+24:00".parse::<Timezone>().is_err());
+        assert!("+02:60".parse::<Timezone>().is_err());
+        assert!("Mars/Olympus_Mons".parse::<Timezone>().is_err());
+    }
+
+    #[test]
+    fn split_a_marker_in_half() {
+        let split = |s| split_marker(s);
+        assert_eq!(split("[]"), ("[".into(), "]".into()));
+        assert_eq!(split("«»"), ("«".into(), "»".into()));
+        assert_eq!(split("****"), ("**".into(), "**".into()));
+        // one that can't be split goes on both sides
+        assert_eq!(split("|"), ("|".into(), "|".into()));
+        assert_eq!(split("_*_"), ("_*_".into(), "_*_".into()));
+    }
+
+    #[test]
+    fn plural_unless_there_is_one() {
+        assert_eq!(plural(0, "quote"), "0 quotes");
+        assert_eq!(plural(1, "quote"), "1 quote");
+        assert_eq!(plural(2, "malformed row"), "2 malformed rows");
+    }
+
+    #[test]
+    fn restoring_the_terminal_resets_the_colors() {
+        let restored = |mut buffer: Buffer, clear| {
+            restore_terminal(&mut buffer, clear).unwrap();
+            String::from_utf8(buffer.into_inner()).unwrap()
+        };
+        assert_eq!(restored(Buffer::ansi(), false), "\x1b[0m");
+        // the cursor was hidden along with clearing the screen
+        assert_eq!(restored(Buffer::ansi(), true), "\x1b[0m\x1b[?25h");
+        assert!(CLEAR_SCREEN.ends_with("\x1b[?25l"));
+        // nothing's written where there are no colors
+        assert_eq!(restored(Buffer::no_color(), true), "");
+    }
+
+    #[test]
+    fn a_pretend_clock_keeps_time_from_where_it_starts() {
+        let start = at(23, 59, 59);
+        let clock = Pretend::new(start);
+        let first = clock.now();
+        std::thread::sleep(Duration::from_millis(20));
+        let later = clock.now();
+        assert!(first >= start && first < start + chrono::Duration::seconds(1));
+        assert!(later > first);
+    }
+
+    #[test]
+    fn exit_codes_for_each_error() {
+        let io = || std::io::Error::other("broken");
+        assert_eq!(exit_code(&Error::Io(io())), EXIT_IO);
+        assert_eq!(exit_code(&Error::Csv(csv::Error::from(io()))), EXIT_IO);
+        assert_eq!(exit_code(&Error::NoQuotes), EXIT_QUOTES);
+        assert_eq!(exit_code(&Error::InvalidTime("25:00".into())), EXIT_QUOTES);
+        let json = serde_json::from_str::<Vec<Quote>>("[").unwrap_err();
+        assert_eq!(exit_code(&Error::Json(json)), EXIT_QUOTES);
+        let config = Config::from_toml("width = \"wide\"").unwrap_err();
+        assert_eq!(exit_code(&config), EXIT_USAGE);
+        assert_eq!(exit_code(&Error::Usage("bad flag".into())), EXIT_USAGE);
+        assert_eq!(
+            exit_code(&Error::NotFound("no quote".into())),
+            EXIT_NOT_FOUND
+        );
+        assert_eq!(exit_code(&Error::NoQuotesIn("a.csv".into())), EXIT_QUOTES);
+        assert_eq!(exit_code(&Error::Problems(2)), EXIT_QUOTES);
+        let file = Error::File("open the log", "log.txt".into(), io());
+        assert_eq!(exit_code(&file), EXIT_IO);
+    }
+
+    #[test]
+    fn parse_author_colors() {
+        let colors =
+            super::parse_author_colors(" Ann Author = blue ; ben writer=#ff8800;").unwrap();
+        assert_eq!(colors.len(), 2);
+        assert_eq!(colors["ann author"], Color::Blue);
+        assert_eq!(colors["ben writer"], Color::Rgb(0xff, 0x88, 0x00));
+
+        assert!(super::parse_author_colors("Ann Author").is_err());
+        assert!(super::parse_author_colors("Ann Author=plaid").is_err());
+    }
+
+    #[test]
+    fn write_the_minutes_remaining() {
+        let remaining = |n| {
+            let mut buffer = Buffer::no_color();
+            write_remaining(&mut buffer, n, &ColorSet::default()).unwrap();
+            String::from_utf8(buffer.into_inner()).unwrap()
+        };
+        assert_eq!(remaining(0), "0 minutes remaining\n");
+        assert_eq!(remaining(1), "1 minute remaining\n");
+        assert_eq!(remaining(90), "90 minutes remaining\n");
     }
-    Err(format!("Unknown color, available: {}", COLORS.join(", ")))
 }
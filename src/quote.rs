@@ -0,0 +1,1506 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::Write;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use termcolor::{Buffer, Color, ColorSpec, WriteColor};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{Error, TimeKey};
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub struct Quote {
+    time: String,
+    context: String,
+    quote: String,
+    source: String,
+    author: String,
+}
+
+impl Quote {
+    /// Fails if the time isn't a 24-hour `HH:MM` timestamp
+    pub fn new(
+        time: impl Into<String>,
+        context: impl Into<String>,
+        quote: impl Into<String>,
+        source: impl Into<String>,
+        author: impl Into<String>,
+    ) -> Result<Self, Error> {
+        let time = time.into();
+        TimeKey::try_from(time.as_str())?;
+        Ok(Self {
+            time,
+            context: context.into(),
+            quote: quote.into(),
+            source: source.into(),
+            author: author.into(),
+        })
+    }
+
+    /// A builder for a quote, where anything left unset is empty
+    pub fn builder() -> QuoteBuilder {
+        QuoteBuilder::default()
+    }
+
+    pub fn time(&self) -> &str {
+        &self.time
+    }
+
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+
+    pub fn quote(&self) -> &str {
+        &self.quote
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    /// How many characters long the quote is
+    pub fn len(&self) -> usize {
+        self.quote.chars().count()
+    }
+
+    /// Whether the quote has no text at all
+    pub fn is_empty(&self) -> bool {
+        self.quote.is_empty()
+    }
+
+    /// Whether the context can be found in the quote
+    pub fn is_highlightable(&self) -> bool {
+        self.highlight_range().is_some()
+    }
+
+    /// The byte range of the context in the quote, ignoring case and whether
+    /// the apostrophes are straight or curly.
+    ///
+    /// A match on word boundaries is preferred, so "ten" is found in "at ten"
+    /// rather than inside of "often"
+    pub fn highlight_range(&self) -> Option<Range<usize>> {
+        let mut found = self.matches();
+        let first = found.next()?;
+        if self.on_boundary(&first) {
+            return Some(first);
+        }
+        found.find(|range| self.on_boundary(range)).or(Some(first))
+    }
+
+    /// Every non-overlapping occurrence of the context in the quote, matched
+    /// like `highlight_range`, so the first is always the one it finds
+    pub fn highlight_ranges(&self) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = vec![];
+        for range in self.matches().filter(|range| self.on_boundary(range)) {
+            if ranges.last().is_none_or(|last| last.end <= range.start) {
+                ranges.push(range);
+            }
+        }
+        if ranges.is_empty() {
+            ranges.extend(self.highlight_range());
+        }
+        ranges
+    }
+
+    /// Every place the context matches in the quote, including ones that overlap
+    fn matches(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.quote
+            .char_indices()
+            .filter(move |_| !self.context.is_empty())
+            .filter_map(move |(start, _)| match_at(&self.quote, start, &self.context))
+    }
+
+    /// Whether the range doesn't start or end in the middle of a word
+    fn on_boundary(&self, range: &Range<usize>) -> bool {
+        let is_word = |ch: Option<char>| ch.is_some_and(char::is_alphanumeric);
+        let (first, last) = (self.context.chars().next(), self.context.chars().last());
+        let joined_before = is_word(first) && is_word(self.quote[..range.start].chars().last());
+        let joined_after = is_word(last) && is_word(self.quote[range.end..].chars().next());
+        !joined_before && !joined_after
+    }
+
+    /// The phrase in the quote closest to the context, for when the context
+    /// can't be found as it's written.
+    ///
+    /// Only a phrase within `max_distance` single character edits of the context
+    /// is used, and only if that's fewer edits than half of the context's length
+    pub fn fuzzy_context(&self, max_distance: usize) -> Option<&str> {
+        let context = self.context.chars().collect::<Vec<_>>();
+        if context.is_empty() || max_distance == 0 {
+            return None;
+        }
+
+        // the edit distance of the context against the phrases that end at
+        // each character, and where the closest of those phrases starts
+        let mut column = (0..=context.len()).map(|i| (i, 0)).collect::<Vec<_>>();
+        let mut best: Option<(usize, Range<usize>)> = None;
+        let bytes = self.quote.char_indices().map(|(i, _)| i);
+        let bytes = bytes.chain(Some(self.quote.len())).collect::<Vec<_>>();
+        for (end, ch) in self.quote.chars().enumerate().map(|(i, ch)| (i + 1, ch)) {
+            let mut next = vec![(0, end)];
+            for (i, &c) in context.iter().enumerate() {
+                let (diagonal, from) = column[i];
+                let cost = if same(c, ch) { 0 } else { 1 };
+                let candidates = [
+                    (diagonal + cost, from),
+                    (next[i].0 + 1, next[i].1),
+                    (column[i + 1].0 + 1, column[i + 1].1),
+                ];
+                next.push(*candidates.iter().min_by_key(|(d, _)| *d).unwrap());
+            }
+            column = next;
+
+            let (distance, start) = column[context.len()];
+            if start < end && best.as_ref().is_none_or(|(d, _)| distance < *d) {
+                best = Some((distance, start..end));
+            }
+        }
+
+        let (_, range) = best.filter(|(d, _)| *d <= max_distance && d * 2 < context.len())?;
+        let phrase = self.quote[bytes[range.start]..bytes[range.end]].trim();
+        Some(phrase).filter(|phrase| !phrase.is_empty())
+    }
+
+    /// The first line of the quote wrapped to the width, ending with `…` if
+    /// the quote doesn't all fit on it
+    pub fn first_line(&self, width: usize) -> String {
+        let quote = self.quote.split_whitespace().collect::<Vec<_>>().join(" ");
+        if quote.width() <= width {
+            return quote;
+        }
+        if width < 2 {
+            return "…".repeat(width);
+        }
+
+        // a column is kept free for the ellipsis
+        let wrapped = textwrap::wrap(&quote, width - 1);
+        let line = wrapped.first().map_or("", |line| line.trim_end());
+        format!("{}…", line)
+    }
+
+    /// As much of the quote as fits in the width, centered on the context so
+    /// the highlight stays visible, with `…` where the quote was cut.
+    ///
+    /// Without a context to center on, the quote is cut from its start, and a
+    /// context that's too wide itself is cut on the right
+    pub fn excerpt(&self, width: usize) -> String {
+        let quote = self.quote.trim();
+        if quote.width() <= width {
+            return quote.to_string();
+        }
+        // there's no room for any of the quote between two ellipses
+        if width < 3 {
+            return "…".repeat(width.min(1));
+        }
+
+        let offset = self.quote.len() - self.quote.trim_start().len();
+        let context = self
+            .highlight_range()
+            .map(|r| r.start.saturating_sub(offset)..r.end.saturating_sub(offset).min(quote.len()));
+        let (mut lo, mut hi, centered) = match context {
+            Some(r) if quote[r.clone()].width() + 2 <= width => (r.start, r.end, true),
+            Some(r) => (r.start, r.start, false),
+            None => (0, 0, false),
+        };
+
+        let ellipses = |lo: usize, hi: usize| (lo > 0) as usize + (hi < quote.len()) as usize;
+        let mut used = quote[lo..hi].width();
+        // grows the window a character at a time on each side, so the context
+        // ends up in the middle
+        loop {
+            let mut grew = false;
+            if let Some(ch) = quote[hi..].chars().next() {
+                let w = ch.width().unwrap_or(0);
+                if used + w + ellipses(lo, hi + ch.len_utf8()) <= width {
+                    hi += ch.len_utf8();
+                    used += w;
+                    grew = true;
+                }
+            }
+            match quote[..lo].chars().next_back() {
+                Some(ch) if centered || !grew => {
+                    let w = ch.width().unwrap_or(0);
+                    if used + w + ellipses(lo - ch.len_utf8(), hi) <= width {
+                        lo -= ch.len_utf8();
+                        used += w;
+                        grew = true;
+                    }
+                }
+                _ => {}
+            }
+            if !grew {
+                break;
+            }
+        }
+
+        let mut out = String::new();
+        if lo > 0 {
+            out.push('…');
+        }
+        out.push_str(quote[lo..hi].trim());
+        if hi < quote.len() {
+            out.push('…');
+        }
+        out
+    }
+
+    /// Returns a copy of the quote that highlights a different phrase
+    pub fn with_context(&self, context: impl Into<String>) -> Self {
+        Self {
+            context: context.into(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the quote where the source doesn't repeat the author,
+    /// or the author the source, e.g. `Kafka, The Trial` by `Franz Kafka`
+    /// becomes just `The Trial`.
+    ///
+    /// The author's surname is looked for too, but only at the start or end
+    pub fn dedup_attribution(&self) -> Self {
+        let (author, source) = (self.author.trim(), self.source.trim());
+        let surname = author
+            .rsplit(' ')
+            .next()
+            .filter(|s| s.len() > 2 && *s != author);
+
+        let source = without_name(source, author)
+            .or_else(|| surname.and_then(|surname| without_name(source, surname)));
+        let author = without_name(author, self.source.trim());
+        Self {
+            source: source.map_or_else(|| self.source.clone(), String::from),
+            author: author.map_or_else(|| self.author.clone(), String::from),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the quote with its text, context, author and source changed
+    pub(crate) fn map_text(&self, f: impl Fn(&str) -> String) -> Self {
+        Self {
+            time: self.time.clone(),
+            context: f(&self.context),
+            quote: f(&self.quote),
+            source: f(&self.source),
+            author: f(&self.author),
+        }
+    }
+
+    pub fn format(
+        &self,
+        stream: &mut Buffer,
+        colors: &ColorSet,
+        layout: &Layout,
+    ) -> Result<(), std::io::Error> {
+        writeln!(stream)?;
+        self.format_quote(stream, colors, layout)?;
+        writeln!(stream)?;
+        self.format_byline(stream, colors, layout)
+    }
+
+    /// Writes the author and source, as they appear under the quote in `format`
+    pub fn format_byline(
+        &self,
+        stream: &mut Buffer,
+        colors: &ColorSet,
+        layout: &Layout,
+    ) -> Result<(), std::io::Error> {
+        let attrib = format!("{} – {}", self.author.trim(), self.source.trim());
+        stream.set_color(&colors.active)?;
+        match layout.align {
+            Align::Left => {
+                for line in layout.wrap_attribution(&attrib, 8) {
+                    writeln!(stream, "{}", line)?;
+                }
+            }
+            align => {
+                let margin = " ".repeat(layout.margin);
+                let width = layout.width.saturating_sub(layout.margin * 2);
+                for line in layout.wrap_attribution(&attrib, 0) {
+                    writeln!(stream, "{}{}", margin, align.pad(line.trim_start(), width))?;
+                }
+            }
+        }
+        stream.reset()
+    }
+
+    /// Like `format`, but without the author and source
+    pub fn format_quote(
+        &self,
+        stream: &mut Buffer,
+        colors: &ColorSet,
+        layout: &Layout,
+    ) -> Result<(), std::io::Error> {
+        for (ch, highlight) in self.wrapped_chars(layout) {
+            if highlight {
+                stream.set_color(&colors.highlight)?;
+            } else {
+                stream.set_color(&colors.inactive)?;
+            }
+            write!(stream, "{}", ch)?;
+            stream.reset()?;
+        }
+
+        writeln!(stream)
+    }
+
+    /// Each character of the wrapped quote, and whether it's part of the context
+    pub fn wrapped_chars(&self, layout: &Layout) -> Vec<(char, bool)> {
+        // the wrapping only changes the whitespace, so the highlight is found
+        // by counting the other characters up to and inside of it
+        let (text, ranges) = self.marked(layout);
+        let visible = |s: &str| s.chars().filter(|ch| !ch.is_whitespace()).count();
+        let spans = ranges
+            .iter()
+            .map(|range| (visible(&text[..range.start]), visible(&text[range.clone()])))
+            .collect::<Vec<_>>();
+
+        let quote = layout
+            .wrap(&text, layout.indent, layout.indent + 2)
+            .join("\n");
+
+        let mut seen = 0;
+        let mut line_start = true;
+        quote
+            .chars()
+            .map(|ch| {
+                let span = spans
+                    .iter()
+                    .find(|&&(skip, len)| seen >= skip && seen < skip + len);
+                if ch == '\n' {
+                    line_start = true;
+                    (ch, false)
+                } else if !ch.is_whitespace() {
+                    line_start = false;
+                    seen += 1;
+                    (ch, span.is_some())
+                } else {
+                    (
+                        ch,
+                        span.is_some_and(|&(skip, _)| seen > skip) && !line_start,
+                    )
+                }
+            })
+            .collect()
+    }
+
+    /// Like `format`, but with the quote on a single line.
+    ///
+    /// The attribution is still aligned against the layout's width, unless it's left aligned
+    pub fn format_no_wrap(
+        &self,
+        stream: &mut Buffer,
+        colors: &ColorSet,
+        layout: &Layout,
+    ) -> Result<(), std::io::Error> {
+        writeln!(stream)?;
+        self.format_quote_no_wrap(stream, colors, layout)?;
+        writeln!(stream)?;
+
+        let attrib = format!("{} – {}", self.author.trim(), self.source.trim());
+        stream.set_color(&colors.active)?;
+        match (layout.align, layout.attribution_width) {
+            (Align::Left, None) => {
+                // the author is right aligned in 20 columns, by how wide it's displayed
+                let author = Align::Right.pad(self.author.trim(), 20);
+                writeln!(stream, "{} – {}", author, self.source)?
+            }
+            (Align::Left, Some(width)) => {
+                // the lines after the first start where the author does
+                let indent = 20usize.saturating_sub(self.author.trim().width());
+                let indent = " ".repeat(indent);
+                let wrapper = textwrap::Wrapper::new(indent.len() + width.max(1))
+                    .initial_indent(&indent)
+                    .subsequent_indent(&indent);
+                for line in wrapper.wrap(&attrib) {
+                    writeln!(stream, "{}", line)?
+                }
+            }
+            (align, Some(width)) => {
+                for line in textwrap::wrap(&attrib, width.max(1)) {
+                    writeln!(stream, "{}", align.pad(&line, layout.width))?
+                }
+            }
+            (align, None) => writeln!(stream, "{}", align.pad(&attrib, layout.width))?,
+        }
+
+        stream.reset()
+    }
+
+    /// Renders the quote like `format`, or like `format_no_wrap`, with the
+    /// colors written as ANSI escapes
+    pub fn render_to_string(&self, colors: &ColorSet, layout: &Layout, no_wrap: bool) -> String {
+        let mut buffer = Buffer::ansi();
+        let written = if no_wrap {
+            self.format_no_wrap(&mut buffer, colors, layout)
+        } else {
+            self.format(&mut buffer, colors, layout)
+        };
+        written.expect("writing to a buffer can't fail");
+        String::from_utf8_lossy(buffer.as_slice()).into_owned()
+    }
+
+    /// Like `format_no_wrap`, but without the author and source
+    pub fn format_quote_no_wrap(
+        &self,
+        stream: &mut Buffer,
+        colors: &ColorSet,
+        layout: &Layout,
+    ) -> Result<(), std::io::Error> {
+        // the context may not appear verbatim in the quote, so just don't highlight it
+        let (text, ranges) = self.marked(layout);
+        let mut written = 0;
+        for Range { start, end } in ranges {
+            stream.set_color(&colors.inactive)?;
+            write!(stream, "{}", &text[written..start])?;
+
+            stream.set_color(&colors.highlight)?;
+            write!(stream, "{}", &text[start..end])?;
+            written = end;
+        }
+
+        stream.set_color(&colors.inactive)?;
+        writeln!(stream, "{}", &text[written..])?;
+        stream.reset()
+    }
+
+    /// The quote with the layout's markers around the context, and where the context is in it
+    fn marked(&self, layout: &Layout) -> (Cow<'_, str>, Vec<Range<usize>>) {
+        let ranges = if layout.highlight_all {
+            self.highlight_ranges()
+        } else {
+            self.highlight_range().into_iter().collect()
+        };
+
+        let (open, close) = match &layout.markers {
+            Some(markers) if !ranges.is_empty() => markers,
+            _ => return (self.quote.as_str().into(), ranges),
+        };
+
+        let (mut text, mut marked, mut written) = (String::new(), vec![], 0);
+        for Range { start, end } in ranges {
+            text.push_str(&self.quote[written..start]);
+            text.push_str(open);
+            let from = text.len();
+            text.push_str(&self.quote[start..end]);
+            marked.push(from..text.len());
+            text.push_str(close);
+            written = end;
+        }
+        text.push_str(&self.quote[written..]);
+        (text.into(), marked)
+    }
+
+    /// Writes just the time and attribution on one line, e.g. `13:37 — Author, Source`
+    pub fn format_attribution(
+        &self,
+        stream: &mut Buffer,
+        colors: &ColorSet,
+        twelve_hour: bool,
+    ) -> Result<(), std::io::Error> {
+        let time = TimeKey::try_from(self.time.as_str())
+            .map_or_else(|_| self.time.clone(), |time| time.format(twelve_hour));
+
+        stream.set_color(&colors.active)?;
+        write!(
+            stream,
+            "{} — {}, {}",
+            time,
+            self.author.trim(),
+            self.source.trim()
+        )?;
+        stream.reset()?;
+        writeln!(stream)
+    }
+}
+
+/// Builds a `Quote` a field at a time, see `Quote::builder`
+#[derive(Debug, Clone, Default)]
+pub struct QuoteBuilder {
+    time: String,
+    context: String,
+    quote: String,
+    source: String,
+    author: String,
+}
+
+impl QuoteBuilder {
+    /// The time, as a 24-hour `HH:MM` timestamp
+    pub fn time(mut self, time: impl Into<String>) -> Self {
+        self.time = time.into();
+        self
+    }
+
+    /// The phrase in the quote that gives the time
+    pub fn context(mut self, context: impl Into<String>) -> Self {
+        self.context = context.into();
+        self
+    }
+
+    pub fn quote(mut self, quote: impl Into<String>) -> Self {
+        self.quote = quote.into();
+        self
+    }
+
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = source.into();
+        self
+    }
+
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = author.into();
+        self
+    }
+
+    /// Fails if the time is missing or isn't a 24-hour `HH:MM` timestamp
+    pub fn build(self) -> Result<Quote, Error> {
+        Quote::new(
+            self.time,
+            self.context,
+            self.quote,
+            self.source,
+            self.author,
+        )
+    }
+}
+
+/// A quote in JSON, either with the same fields as `Quote` or in the shape used
+/// by the literature clock, where the quote is split around the time phrase
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum JsonQuote {
+    Quote(Quote),
+    Split {
+        time: String,
+        quote_first: String,
+        quote_time_case: String,
+        quote_last: String,
+        title: String,
+        author: String,
+    },
+}
+
+impl From<JsonQuote> for Quote {
+    fn from(quote: JsonQuote) -> Self {
+        match quote {
+            JsonQuote::Quote(quote) => quote,
+            JsonQuote::Split {
+                time,
+                quote_first,
+                quote_time_case,
+                quote_last,
+                title,
+                author,
+            } => Self {
+                time,
+                quote: format!("{}{}{}", quote_first, quote_time_case, quote_last),
+                context: quote_time_case,
+                source: title,
+                author,
+            },
+        }
+    }
+}
+
+/// Renders the quote and attribution as plain text, without any colors.
+///
+/// The quote is wrapped to the formatting width, e.g. `{:40}`, or to 60 columns
+impl std::fmt::Display for Quote {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut buffer = Buffer::no_color();
+        let layout = Layout {
+            width: f.width().unwrap_or(60),
+            ..Layout::default()
+        };
+        self.format(&mut buffer, &ColorSet::default(), &layout)
+            .map_err(|_| std::fmt::Error)?;
+        f.write_str(String::from_utf8_lossy(buffer.as_slice()).trim_matches('\n'))
+    }
+}
+
+/// Matches the needle at the start of the haystack, returning the byte range it covers
+fn match_at(haystack: &str, start: usize, needle: &str) -> Option<Range<usize>> {
+    let mut rest = haystack[start..].char_indices();
+    for n in needle.chars() {
+        match rest.next() {
+            Some((_, h)) if same(h, n) => {}
+            _ => return None,
+        }
+    }
+    let end = rest.next().map_or(haystack.len(), |(i, _)| start + i);
+    Some(start..end)
+}
+
+/// The field with the name cut from its start or end, along with whatever
+/// separated them, if there's anything left
+fn without_name<'a>(field: &'a str, name: &str) -> Option<&'a str> {
+    let separator = |c: char| c.is_whitespace() || ",:;()[]-–—/".contains(c);
+    // just a space isn't enough, `The Autobiography of Malcolm X` and
+    // `Parkinson's Law` have to stay whole
+    let separated = |between: &str| between.chars().any(|c| !c.is_whitespace());
+    let matches = |part: Option<&str>| part.is_some_and(|part| part.eq_ignore_ascii_case(name));
+
+    if name.is_empty() || field.len() <= name.len() {
+        return None;
+    }
+    // a name at the end can be in brackets
+    let unbracketed = field.trim_end_matches([')', ']']);
+
+    let rest = if matches(field.get(..name.len())) {
+        let rest = &field[name.len()..];
+        let trimmed = rest.trim_start_matches(separator);
+        if !separated(&rest[..rest.len() - trimmed.len()]) {
+            return None;
+        }
+        trimmed
+    } else if matches(unbracketed.get(unbracketed.len().saturating_sub(name.len())..)) {
+        let rest = &unbracketed[..unbracketed.len() - name.len()];
+        let before = rest.trim_end();
+        let by = before.get(before.len().saturating_sub(3)..);
+        match by.filter(|by| by.eq_ignore_ascii_case(" by")) {
+            Some(..) => before[..before.len() - 3].trim_end_matches(separator),
+            None => {
+                let trimmed = rest.trim_end_matches(separator);
+                if !separated(&rest[trimmed.len()..]) {
+                    return None;
+                }
+                trimmed
+            }
+        }
+    } else {
+        return None;
+    };
+    Some(rest).filter(|rest| rest.chars().any(char::is_alphanumeric))
+}
+
+/// Whether the characters are the same, ignoring case and treating a curly
+/// apostrophe the same as a straight one
+fn same(a: char, b: char) -> bool {
+    let fold = |ch: char| if ch == '’' { '\'' } else { ch };
+    fold(a).to_lowercase().eq(fold(b).to_lowercase())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ColorSet {
+    pub active: ColorSpec,
+    pub inactive: ColorSpec,
+    pub highlight: ColorSpec,
+}
+
+impl ColorSet {
+    /// The names of the built-in themes, for `theme`
+    pub const THEMES: [&'static str; 4] = ["default", "mono", "solarized", "highcontrast"];
+
+    /// Looks up a built-in theme by its name
+    pub fn theme(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "default" => Some(Self::standard()),
+            "mono" => Some(Self::mono()),
+            "solarized" => Some(Self::solarized()),
+            "highcontrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// Bright red for the time phrase, on white text
+    pub fn standard() -> Self {
+        Self {
+            highlight: spec(Some(Color::Red), None).set_intense(true).clone(),
+            inactive: spec(Some(Color::White), None),
+            active: spec(Some(Color::White), None).set_intense(true).clone(),
+        }
+    }
+
+    /// No colors at all, just bold and underlines, for any terminal's own colors
+    pub fn mono() -> Self {
+        Self {
+            highlight: spec(None, None).set_bold(true).set_underline(true).clone(),
+            inactive: spec(None, None),
+            active: spec(None, None).set_italic(true).clone(),
+        }
+    }
+
+    /// The colors of the solarized palette
+    pub fn solarized() -> Self {
+        Self {
+            highlight: spec(Some(Color::Rgb(203, 75, 22)), None)
+                .set_bold(true)
+                .clone(),
+            inactive: spec(Some(Color::Rgb(131, 148, 150)), None),
+            active: spec(Some(Color::Rgb(38, 139, 210)), None),
+        }
+    }
+
+    /// Black on yellow for the time phrase, on bright white text
+    pub fn high_contrast() -> Self {
+        Self {
+            highlight: spec(Some(Color::Black), Some(Color::Yellow))
+                .set_bold(true)
+                .clone(),
+            inactive: spec(Some(Color::White), None).set_intense(true).clone(),
+            active: spec(Some(Color::Cyan), None)
+                .set_intense(true)
+                .set_bold(true)
+                .clone(),
+        }
+    }
+}
+
+fn spec(fg: Option<Color>, bg: Option<Color>) -> ColorSpec {
+    let mut spec = ColorSpec::new();
+    spec.set_fg(fg).set_bg(bg);
+    spec
+}
+
+/// Where quotes are wrapped and how they're indented
+#[derive(Debug, Clone)]
+pub struct Layout {
+    pub width: usize,
+    /// How many columns are left empty on either side
+    pub margin: usize,
+    /// How far the first line of the quote is indented, the rest are indented 2 more
+    pub indent: usize,
+    /// How the author and source are aligned
+    pub align: Align,
+    /// Put before and after the context, to mark it without colors
+    pub markers: Option<(String, String)>,
+    /// Whether every occurrence of the context is highlighted, rather than just the first
+    pub highlight_all: bool,
+    /// The widest the author and source can be before they wrap, rather than
+    /// only wrapping at the width
+    pub attribution_width: Option<usize>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            width: 60,
+            margin: 0,
+            indent: 2,
+            align: Align::Left,
+            markers: None,
+            highlight_all: false,
+            attribution_width: None,
+        }
+    }
+}
+
+impl Layout {
+    /// Wraps the text between the margins, indenting the first line by `first`
+    /// spaces and the rest by `rest`.
+    ///
+    /// At least one column is always left for the text, so a tiny width still
+    /// wraps one character per line rather than not at all
+    fn wrap(&self, text: &str, first: usize, rest: usize) -> Vec<String> {
+        let (first, rest) = (self.margin + first, self.margin + rest);
+        let width = self
+            .width
+            .saturating_sub(self.margin)
+            .max(first.max(rest) + 1);
+
+        let (first, rest) = (" ".repeat(first), " ".repeat(rest));
+        textwrap::Wrapper::new(width)
+            .initial_indent(&first)
+            .subsequent_indent(&rest)
+            .wrap(text)
+            .into_iter()
+            .map(|line| line.into_owned())
+            .collect()
+    }
+
+    /// Wraps the author and source like `wrap`, but no wider than the
+    /// attribution's width, with every line indented by `indent`
+    fn wrap_attribution(&self, text: &str, indent: usize) -> Vec<String> {
+        match self.attribution_width {
+            // `wrap` only takes the margin off of the right side once
+            Some(width) => Self {
+                width: self.width.min(self.margin * 2 + indent + width),
+                ..self.clone()
+            }
+            .wrap(text, indent, indent),
+            None => self.wrap(text, indent, indent),
+        }
+    }
+}
+
+/// How the author and source line up under the quote
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Align {
+    /// Indented by 8 spaces
+    Left,
+    Right,
+    Center,
+}
+
+impl Align {
+    /// Pads the line so it's aligned within the width
+    fn pad(self, line: &str, width: usize) -> String {
+        let space = width.saturating_sub(line.width());
+        match self {
+            Align::Left => line.to_string(),
+            Align::Right => format!("{}{}", " ".repeat(space), line),
+            Align::Center => format!("{}{}", " ".repeat(space / 2), line),
+        }
+    }
+}
+
+impl std::str::FromStr for Align {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "left" => Ok(Align::Left),
+            "right" => Ok(Align::Right),
+            "center" | "centre" => Ok(Align::Center),
+            _ => Err(String::from(
+                "Unknown alignment, available: left, right, center",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote(context: &str, text: &str) -> Quote {
+        Quote::new("10:00", context, text, "Source", "Author").unwrap()
+    }
+
+    /// What's written to switch to the spec, to look for in the rendered quote
+    fn escape(spec: &ColorSpec) -> String {
+        let mut buffer = Buffer::ansi();
+        buffer.set_color(spec).unwrap();
+        String::from_utf8(buffer.into_inner()).unwrap()
+    }
+
+    fn no_wrap(quote: &Quote, colors: &ColorSet) -> String {
+        let mut buffer = Buffer::ansi();
+        quote
+            .format_no_wrap(&mut buffer, colors, &Layout::default())
+            .unwrap();
+        String::from_utf8(buffer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn no_wrap_highlights_context_with_a_curly_apostrophe() {
+        let quote = quote("ten o’clock", "It was ten o'clock at night.");
+        let colors = ColorSet::standard();
+        let out = no_wrap(&quote, &colors);
+        assert!(out.contains(&format!("{}ten o'clock", escape(&colors.highlight))));
+    }
+
+    #[test]
+    fn no_wrap_without_the_context_prints_the_whole_quote() {
+        let quote = quote("midnight", "It was ten o'clock at night.");
+        let colors = ColorSet::standard();
+        let out = no_wrap(&quote, &colors);
+        assert!(out.contains("It was ten o'clock at night."));
+        assert!(!out.contains(&escape(&colors.highlight)));
+    }
+
+    #[test]
+    fn no_wrap_highlights_between_multibyte_characters() {
+        let quote = quote("midi", "Il était midi à Paris");
+        let colors = ColorSet::standard();
+        let out = no_wrap(&quote, &colors);
+        let highlight = format!("{}midi", escape(&colors.highlight));
+        let after = format!("{} à Paris", escape(&colors.inactive));
+        assert!(out.contains(&highlight));
+        assert!(out.contains(&after));
+        assert!(out.contains("Il était "));
+    }
+
+    #[test]
+    fn highlights_the_context_as_a_word_rather_than_inside_one() {
+        let quote = quote("ten", "She often came at ten.");
+        let start = "She often came at ".len();
+        assert_eq!(quote.highlight_range(), Some(start..start + 3));
+
+        let highlighted = quote
+            .wrapped_chars(&Layout::default())
+            .into_iter()
+            .filter(|&(_, highlight)| highlight)
+            .map(|(ch, _)| ch)
+            .collect::<String>();
+        assert_eq!(highlighted, "ten");
+    }
+
+    #[test]
+    fn highlights_inside_a_word_when_thats_the_only_match() {
+        let quote = quote("ten", "She often came.");
+        assert_eq!(quote.highlight_range(), Some(6..9));
+    }
+
+    #[test]
+    fn background_is_only_behind_the_context() {
+        let quote = quote("noon", "It was noon at last.");
+        let mut colors = ColorSet::standard();
+        colors.highlight.set_bg(Some(Color::Blue));
+
+        let mut buffer = Buffer::ansi();
+        quote
+            .format_quote(&mut buffer, &colors, &Layout::default())
+            .unwrap();
+        let out = String::from_utf8(buffer.into_inner()).unwrap();
+
+        let (highlight, inactive) = (escape(&colors.highlight), escape(&colors.inactive));
+        // each character gets its own color
+        assert_eq!(out.matches(&highlight).count(), "noon".len());
+        for ch in "noon".chars() {
+            assert!(out.contains(&format!("{}{}", highlight, ch)));
+        }
+        assert!(out.contains(&format!("{}I", inactive)));
+        assert!(!inactive.contains("\x1b[44m"));
+    }
+
+    #[test]
+    fn display_is_plain_text_with_the_attribution() {
+        let quote = Quote::new("12:00", "noon", "It was noon.", "Emma ", "Jane Austen ").unwrap();
+        let text = quote.to_string();
+        assert!(!text.contains('\x1b'));
+        assert!(text.contains("It was noon."));
+        assert!(text.contains("Jane Austen – Emma"));
+        assert!(!text.starts_with('\n') && !text.ends_with('\n'));
+
+        let narrow = format!("{:10}", quote);
+        assert!(narrow.lines().count() > text.lines().count());
+    }
+
+    #[test]
+    fn highlightable_when_the_context_is_in_the_quote() {
+        assert!(quote("noon", "It was NOON.").is_highlightable());
+        assert!(quote("twelve o'clock", "At twelve o’clock.").is_highlightable());
+        assert!(!quote("midnight", "It was noon.").is_highlightable());
+        assert!(!quote("", "It was noon.").is_highlightable());
+    }
+
+    #[test]
+    fn highlight_range_is_the_byte_range_of_the_context() {
+        let range = |context, text| quote(context, text).highlight_range();
+        assert_eq!(range("noon", "It was noon."), Some(7..11));
+        assert_eq!(range("NOON", "It was Noon."), Some(7..11));
+
+        // either apostrophe matches either apostrophe, though they're different lengths
+        let text = "At one o’clock.";
+        assert_eq!(range("one o'clock", text), Some(3..16));
+        assert_eq!(&text[3..16], "one o’clock");
+        assert_eq!(range("one o’clock", "At one o'clock."), Some(3..14));
+
+        // the range is in bytes, even after accented characters
+        let text = "Café à midi, l’été.";
+        let found = range("MIDI", text).unwrap();
+        assert_eq!(found, 9..13);
+        assert_eq!(&text[found], "midi");
+        assert_eq!(range("l'ÉTÉ", text).map(|r| &text[r]), Some("l’été"));
+
+        assert_eq!(range("midnight", "It was noon."), None);
+        assert_eq!(range("", "It was noon."), None);
+    }
+
+    #[test]
+    fn mixed_apostrophes_highlight_what_is_printed() {
+        // the quote has both styles, and so does the context, the other way around
+        let quote = quote(
+            "the clock’s hands at one o'clock",
+            "Don't wind the clock's hands at one o’clock, she’d said.",
+        );
+        let text = quote.quote();
+        let range = quote.highlight_range().unwrap();
+        assert_eq!(&text[range.clone()], "the clock's hands at one o’clock");
+
+        // the highlight lines up with the original characters, wrapped or not
+        let highlighted = quote
+            .wrapped_chars(&Layout::default())
+            .into_iter()
+            .filter(|&(_, highlight)| highlight)
+            .map(|(ch, _)| ch)
+            .collect::<String>();
+        assert_eq!(highlighted, &text[range.clone()]);
+
+        let colors = ColorSet::standard();
+        let out = no_wrap(&quote, &colors);
+        let expected = format!(
+            "{}{}{}{}",
+            escape(&colors.highlight),
+            &text[range.clone()],
+            escape(&colors.inactive),
+            &text[range.end..]
+        );
+        assert!(out.contains(&expected));
+        assert!(out.contains("Don't wind "));
+    }
+
+    #[test]
+    fn the_attribution_is_aligned_within_the_width() {
+        let quote = Quote::new("12:00", "noon", "Noon.", "Emma", "Jane Austen").unwrap();
+        let byline = |align| {
+            let layout = Layout {
+                width: 40,
+                align,
+                ..Layout::default()
+            };
+            let mut buffer = Buffer::no_color();
+            quote
+                .format_byline(&mut buffer, &ColorSet::default(), &layout)
+                .unwrap();
+            String::from_utf8(buffer.into_inner()).unwrap()
+        };
+        // "Jane Austen – Emma" is 18 columns
+        assert_eq!(
+            byline(Align::Left),
+            format!("{}Jane Austen – Emma\n", " ".repeat(8))
+        );
+        assert_eq!(
+            byline(Align::Right),
+            format!("{}Jane Austen – Emma\n", " ".repeat(22))
+        );
+        assert_eq!(
+            byline(Align::Center),
+            format!("{}Jane Austen – Emma\n", " ".repeat(11))
+        );
+    }
+
+    #[test]
+    fn the_quote_is_indented() {
+        let quote = quote("noon", "It was noon, and the square was empty.");
+        let lines = |indent| {
+            let layout = Layout {
+                width: 24,
+                indent,
+                ..Layout::default()
+            };
+            let mut buffer = Buffer::no_color();
+            quote
+                .format_quote(&mut buffer, &ColorSet::default(), &layout)
+                .unwrap();
+            String::from_utf8(buffer.into_inner()).unwrap()
+        };
+        // the lines after the first are indented 2 more
+        assert_eq!(lines(2), "  It was noon, and the\n    square was empty.\n");
+        assert_eq!(lines(0), "It was noon, and the\n  square was empty.\n");
+        assert_eq!(
+            lines(6),
+            "      It was noon, and\n        the square was\n        empty.\n"
+        );
+    }
+
+    #[test]
+    fn parse_align() {
+        assert_eq!("Right".parse(), Ok(Align::Right));
+        assert_eq!("centre".parse(), Ok(Align::Center));
+        assert_eq!("left".parse(), Ok(Align::Left));
+        assert!("middle".parse::<Align>().is_err());
+    }
+
+    #[test]
+    fn tiny_widths_still_wrap() {
+        let quote = Quote::new(
+            "12:00",
+            "noon",
+            "It was noon, and the square was empty.",
+            "Square Book",
+            "Ben Writer",
+        )
+        .unwrap();
+        let rendered = |width, margin| {
+            let layout = Layout {
+                width,
+                margin,
+                ..Layout::default()
+            };
+            let mut buffer = Buffer::no_color();
+            quote
+                .format(&mut buffer, &ColorSet::default(), &layout)
+                .unwrap();
+            String::from_utf8(buffer.into_inner()).unwrap()
+        };
+        let words = |s: &str| s.split_whitespace().collect::<String>();
+
+        for &width in &[1, 4, 80] {
+            let out = rendered(width, 0);
+            // nothing is lost
+            let all = "It was noon, and the square was empty. Ben Writer – Square Book";
+            assert_eq!(words(&out), words(all));
+            // a quote line is no wider than the width, or the indent and one
+            // character, so there's always something after the indent
+            let quote_lines = out.lines().skip(1).take_while(|line| !line.is_empty());
+            for line in quote_lines {
+                assert!(line.width() <= width.max(5), "{:?} at {}", line, width);
+                assert!(!line.trim().is_empty());
+            }
+        }
+        assert_eq!(
+            rendered(80, 0).lines().nth(1),
+            Some("  It was noon, and the square was empty.")
+        );
+
+        // the margin is taken off of both sides
+        let out = rendered(30, 4);
+        let quote_lines = out.lines().skip(1).take_while(|line| !line.is_empty());
+        for line in quote_lines {
+            assert!(line.starts_with("      "));
+            assert!(line.width() <= 26, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn wide_characters_are_wrapped_by_their_width() {
+        let quote = Quote::new(
+            "12:00",
+            "正午",
+            "正午の鐘が鳴り、広場には誰もいなかった。それから長い午後が始まった。",
+            "時計の本",
+            "作家",
+        )
+        .unwrap();
+        let layout = Layout {
+            width: 20,
+            align: Align::Right,
+            ..Layout::default()
+        };
+        let mut buffer = Buffer::no_color();
+        quote
+            .format(&mut buffer, &ColorSet::default(), &layout)
+            .unwrap();
+        let out = String::from_utf8(buffer.into_inner()).unwrap();
+
+        let lines = out
+            .lines()
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+        assert!(lines.len() > 3);
+        for line in &lines {
+            assert!(line.width() <= 20, "{:?} is {} columns", line, line.width());
+        }
+        // each of these characters takes two columns, so it's right aligned
+        // with fewer spaces than it has characters
+        let byline = lines.last().unwrap();
+        assert_eq!(byline.width(), 20);
+        assert_eq!(byline.trim_start(), "作家 – 時計の本");
+        assert_eq!(byline.len() - byline.trim_start().len(), 5);
+    }
+
+    #[test]
+    fn len_is_in_characters() {
+        assert_eq!(quote("", "noon").len(), 4);
+        assert_eq!(quote("", "midi à l’été").len(), 12);
+        assert!(quote("", "").is_empty());
+    }
+
+    #[test]
+    fn a_context_one_character_off_is_found_fuzzily() {
+        let text = "It was ten past twelve, and she was late.";
+        assert_eq!(
+            quote("ten past twelfe", text).fuzzy_context(2),
+            Some("ten past twelve")
+        );
+
+        let typo = quote("ten pst twelve", text);
+        assert_eq!(typo.fuzzy_context(1), Some("ten past twelve"));
+
+        // the phrase it finds is highlighted like any other context
+        let fixed = typo.with_context(typo.fuzzy_context(1).unwrap());
+        assert_eq!(&text[fixed.highlight_range().unwrap()], "ten past twelve");
+    }
+
+    #[test]
+    fn a_fuzzy_match_has_to_be_close() {
+        let text = "It was ten past twelve, and she was late.";
+        // 0 turns it off
+        assert_eq!(quote("ten pst twelve", text).fuzzy_context(0), None);
+        // too many edits
+        assert_eq!(quote("tan pst twalve", text).fuzzy_context(2), None);
+        // half of a short context is too much of it
+        assert_eq!(quote("xt", text).fuzzy_context(2), None);
+        assert_eq!(quote("", text).fuzzy_context(2), None);
+    }
+
+    #[test]
+    fn highlight_all_colors_every_occurrence() {
+        let quote = quote("noon", "Noon, and at noon the bells rang for noonday.");
+        assert_eq!(quote.highlight_ranges(), [0..4, 13..17]);
+        // just the first by default
+        assert_eq!(quote.highlight_range(), Some(0..4));
+
+        let highlighted = |highlight_all| {
+            let layout = Layout {
+                highlight_all,
+                ..Layout::default()
+            };
+            let chars = quote.wrapped_chars(&layout);
+            let mut words = vec![];
+            for (i, &(ch, highlight)) in chars.iter().enumerate() {
+                if highlight {
+                    let starts = i == 0 || !chars[i - 1].1;
+                    if starts {
+                        words.push(String::new());
+                    }
+                    words.last_mut().unwrap().push(ch);
+                }
+            }
+            words
+        };
+        assert_eq!(highlighted(true), ["Noon", "noon"]);
+        assert_eq!(highlighted(false), ["Noon"]);
+
+        let colors = ColorSet::standard();
+        let layout = Layout {
+            highlight_all: true,
+            ..Layout::default()
+        };
+        let mut buffer = Buffer::ansi();
+        quote.format_no_wrap(&mut buffer, &colors, &layout).unwrap();
+        let out = String::from_utf8(buffer.into_inner()).unwrap();
+        let highlight = escape(&colors.highlight);
+        assert!(out.contains(&format!("{}Noon", highlight)));
+        assert!(out.contains(&format!("{}noon", highlight)));
+        assert_eq!(out.matches(&highlight).count(), 2);
+    }
+
+    #[test]
+    fn overlapping_occurrences_are_highlighted_once() {
+        let quote = quote("ha ha", "He laughed, ha ha ha.");
+        assert_eq!(quote.highlight_ranges(), vec![12..17]);
+    }
+
+    #[test]
+    fn the_solarized_theme() {
+        let theme = ColorSet::theme("Solarized").unwrap();
+        let mut highlight = ColorSpec::new();
+        highlight
+            .set_fg(Some(Color::Rgb(203, 75, 22)))
+            .set_bold(true);
+        assert_eq!(theme.highlight, highlight);
+        assert_eq!(theme.inactive, spec(Some(Color::Rgb(131, 148, 150)), None));
+        assert_eq!(theme.active, spec(Some(Color::Rgb(38, 139, 210)), None));
+    }
+
+    #[test]
+    fn every_theme_can_be_looked_up() {
+        for name in &ColorSet::THEMES {
+            assert!(ColorSet::theme(name).is_some(), "no {} theme", name);
+        }
+        assert_eq!(
+            ColorSet::theme("default").unwrap().highlight,
+            ColorSet::standard().highlight
+        );
+        assert!(ColorSet::theme("neon").is_none());
+    }
+
+    #[test]
+    fn a_long_attribution_wraps_within_its_width() {
+        let quote = Quote::new(
+            "12:00",
+            "noon",
+            "It was noon.",
+            "The Extremely Long and Winding Title of a Book About Clocks",
+            "Jane Austen",
+        )
+        .unwrap();
+        let layout = Layout {
+            width: 80,
+            attribution_width: Some(24),
+            ..Layout::default()
+        };
+        let attribution = |no_wrap| {
+            let mut buffer = Buffer::no_color();
+            let colors = ColorSet::default();
+            if no_wrap {
+                quote.format_no_wrap(&mut buffer, &colors, &layout).unwrap();
+            } else {
+                quote.format(&mut buffer, &colors, &layout).unwrap();
+            }
+            let rendered = String::from_utf8(buffer.into_inner()).unwrap();
+            let lines = rendered
+                .lines()
+                .skip_while(|line| !line.contains("Jane Austen"));
+            lines.map(String::from).collect::<Vec<_>>()
+        };
+
+        let wrapped = attribution(false);
+        assert!(wrapped.len() > 2);
+        for line in &wrapped {
+            assert!(line.starts_with("        "), "{:?}", line);
+            assert!(line.width() <= 8 + 24, "{:?}", line);
+        }
+
+        // without wrapping the quote, the lines line up under the author
+        let unwrapped = attribution(true);
+        assert!(unwrapped.len() > 2);
+        assert!(unwrapped[0].starts_with("         Jane Austen – "));
+        for line in &unwrapped {
+            assert!(line.starts_with("         "), "{:?}", line);
+            assert!(line.width() <= 9 + 24, "{:?}", line);
+        }
+        let words = |lines: &[String]| lines.concat().split_whitespace().collect::<String>();
+        assert_eq!(words(&wrapped), words(&unwrapped));
+    }
+
+    #[test]
+    fn render_to_string_is_what_format_writes() {
+        let quote = quote(
+            "ten o'clock",
+            "At ten o'clock the house was quiet, and the long hall was dark.",
+        );
+        let colors = ColorSet::standard();
+        let layout = Layout {
+            width: 30,
+            ..Layout::default()
+        };
+
+        let mut buffer = Buffer::ansi();
+        quote.format(&mut buffer, &colors, &layout).unwrap();
+        let wrapped = quote.render_to_string(&colors, &layout, false);
+        assert_eq!(wrapped.as_bytes(), buffer.as_slice());
+        assert!(wrapped.lines().count() > 3);
+
+        let mut buffer = Buffer::ansi();
+        quote.format_no_wrap(&mut buffer, &colors, &layout).unwrap();
+        let line = quote.render_to_string(&colors, &layout, true);
+        assert_eq!(line.as_bytes(), buffer.as_slice());
+        assert_ne!(line, wrapped);
+    }
+
+    #[test]
+    fn the_first_line_ends_with_an_ellipsis_when_cut() {
+        let quote = quote("ten", "It was ten o'clock\n  and the hall was dark.");
+        // the whole quote fits, on one line
+        assert_eq!(
+            quote.first_line(60),
+            "It was ten o'clock and the hall was dark."
+        );
+        assert_eq!(quote.first_line(41), quote.first_line(60));
+
+        let line = quote.first_line(20);
+        assert_eq!(line, "It was ten o'clock…");
+        assert!(line.width() <= 20);
+        assert_eq!(quote.first_line(5), "It…");
+        // a word longer than the width is broken
+        let long = self::quote("late", "Extraordinarily late.");
+        assert_eq!(long.first_line(6), "Extra…");
+        assert_eq!(quote.first_line(1), "…");
+        assert_eq!(quote.first_line(0), "");
+    }
+
+    #[test]
+    fn an_excerpt_keeps_the_context_in_view() {
+        let quote = quote(
+            "ten o'clock",
+            "The house was long asleep, and at ten o'clock the lamps went out one after another.",
+        );
+        let excerpt = quote.excerpt(25);
+        assert_eq!(excerpt, "…nd at ten o'clock the l…");
+        assert_eq!(excerpt.width(), 25);
+        // the context is about in the middle
+        let at = excerpt.find("ten o'clock").unwrap();
+        let after = excerpt.len() - at - "ten o'clock".len();
+        assert!((at as isize - after as isize).abs() <= 4);
+
+        // it all fits
+        assert_eq!(quote.excerpt(100), quote.quote());
+        // one too wide to fit is cut from its start
+        assert_eq!(quote.excerpt(11), "…ten o'clo…");
+        assert_eq!(quote.excerpt(2), "…");
+
+        // without a context it's the start of the quote
+        let plain = self::quote("midnight", "Nothing happened that day, or the next.");
+        assert_eq!(plain.excerpt(12), "Nothing hap…");
+    }
+
+    #[test]
+    fn a_repeated_name_is_taken_out_of_the_attribution() {
+        let dedup = |author: &str, source: &str| {
+            let quote = Quote::new("10:00", "", "x", source, author)
+                .unwrap()
+                .dedup_attribution();
+            (quote.author().to_string(), quote.source().to_string())
+        };
+        let collapsed = |author: &str, source: &str| (author.to_string(), source.to_string());
+
+        let kafka = collapsed("Franz Kafka", "The Trial");
+        assert_eq!(dedup("Franz Kafka", "Kafka, The Trial"), kafka);
+        assert_eq!(dedup("Franz Kafka", "The Trial - Franz Kafka"), kafka);
+        assert_eq!(dedup("Franz Kafka", "The Trial (Kafka)"), kafka);
+        assert_eq!(
+            dedup(
+                "Agatha Christie",
+                "The Murder at the Vicarage by Agatha Christie"
+            ),
+            collapsed("Agatha Christie", "The Murder at the Vicarage")
+        );
+        // or the author repeats the source
+        assert_eq!(
+            dedup("Anonymous, The Sagas", "The Sagas"),
+            collapsed("Anonymous", "The Sagas")
+        );
+
+        // a name that's part of the title is left alone
+        for &(author, source) in &[
+            ("Malcolm X", "The Autobiography of Malcolm X"),
+            ("C. Northcote Parkinson", "Parkinson's Law"),
+            ("Jules Verne", "A Journey to the Centre of the Earth"),
+        ] {
+            assert_eq!(dedup(author, source), collapsed(author, source));
+        }
+    }
+
+    #[test]
+    fn a_built_quote_can_be_put_in_a_database() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let quote = Quote::builder()
+            .time("07:45")
+            .context("quarter to eight")
+            .quote("At a quarter to eight the milk was left.")
+            .source("Mornings")
+            .author("Kit Writer")
+            .build()
+            .unwrap();
+        assert_eq!(
+            quote,
+            Quote::new(
+                "07:45",
+                "quarter to eight",
+                "At a quarter to eight the milk was left.",
+                "Mornings",
+                "Kit Writer",
+            )
+            .unwrap()
+        );
+
+        let quotes = vec![
+            quote,
+            Quote::builder()
+                .time("19:00")
+                .quote("Seven.")
+                .build()
+                .unwrap(),
+        ];
+        let db = crate::Database::new(&quotes);
+        assert_eq!(db.len(), 2);
+        let time = TimeKey::try_from("07:45").unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        let found = db.at_time(time, &mut rng).unwrap();
+        assert_eq!(found.author(), "Kit Writer");
+        assert_eq!(found.highlight_range(), Some(5..21));
+    }
+
+    #[test]
+    fn a_quote_needs_a_valid_time() {
+        let quote = || Quote::builder().quote("Whenever.").author("Author");
+        assert!(matches!(quote().build(), Err(Error::InvalidTime(..))));
+        assert!(matches!(
+            quote().time("25:00").build(),
+            Err(Error::InvalidTime(..))
+        ));
+        assert!(matches!(
+            Quote::new("7:45", "", "", "", ""),
+            Err(Error::InvalidTime(..))
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+use std::convert::TryFrom;
+
+use crate::Error;
+
+/// A minute of the day, ordered from `00:00` to `23:59`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeKey {
+    hour: u8,
+    minute: u8,
+}
+
+impl TimeKey {
+    /// Returns `None` if the hour isn't in `0..=23` or the minute isn't in `0..=59`
+    pub fn new(hour: u8, minute: u8) -> Option<Self> {
+        if hour < 24 && minute < 60 {
+            Some(Self { hour, minute })
+        } else {
+            None
+        }
+    }
+
+    pub fn hour(self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(self) -> u8 {
+        self.minute
+    }
+
+    /// The next minute, wrapping around to `00:00` after `23:59`
+    pub fn succ(self) -> Self {
+        match (self.hour, self.minute) {
+            (23, 59) => Self::new(0, 0),
+            (hour, 59) => Self::new(hour + 1, 0),
+            (hour, minute) => Self::new(hour, minute + 1),
+        }
+        .expect("valid time")
+    }
+
+    /// The previous minute, wrapping around to `23:59` before `00:00`
+    pub fn pred(self) -> Self {
+        match (self.hour, self.minute) {
+            (0, 0) => Self::new(23, 59),
+            (hour, 0) => Self::new(hour - 1, 59),
+            (hour, minute) => Self::new(hour, minute - 1),
+        }
+        .expect("valid time")
+    }
+
+    /// How many minutes it is from this time until the other, which is taken
+    /// to be the next day if it's earlier
+    pub fn minutes_until(self, other: Self) -> usize {
+        const MINUTES_PER_DAY: usize = 24 * 60;
+        let minutes = |t: Self| usize::from(t.hour) * 60 + usize::from(t.minute);
+        (MINUTES_PER_DAY + minutes(other) - minutes(self)) % MINUTES_PER_DAY
+    }
+
+    /// Formats the time as either `HH:MM` or as a 12-hour clock time, e.g. `1:37 PM`
+    pub fn format(self, twelve_hour: bool) -> String {
+        if !twelve_hour {
+            return self.to_string();
+        }
+
+        let (hh, suffix) = match self.hour {
+            0 => (12, "AM"),
+            hh @ 1..=11 => (hh, "AM"),
+            12 => (12, "PM"),
+            hh => (hh - 12, "PM"),
+        };
+        format!("{}:{:02} {}", hh, self.minute, suffix)
+    }
+}
+
+/// Parses a 24-hour `HH:MM` timestamp, with exactly two digits on each side
+impl TryFrom<&str> for TimeKey {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let digits = |d: &str| {
+            Some(d)
+                .filter(|d| d.len() == 2 && d.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|d| d.parse::<u8>().ok())
+        };
+        let mut parts = s.split(':').map(digits);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Some(hh)), Some(Some(mm)), None) => Self::new(hh, mm),
+            _ => None,
+        }
+        .ok_or_else(|| Error::InvalidTime(s.to_string()))
+    }
+}
+
+impl std::fmt::Display for TimeKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> TimeKey {
+        TimeKey::try_from(s).unwrap()
+    }
+
+    #[test]
+    fn twelve_hour_clock() {
+        assert_eq!(time("00:00").format(true), "12:00 AM");
+        assert_eq!(time("00:30").format(true), "12:30 AM");
+        assert_eq!(time("01:05").format(true), "1:05 AM");
+        assert_eq!(time("12:00").format(true), "12:00 PM");
+        assert_eq!(time("13:37").format(true), "1:37 PM");
+        assert_eq!(time("23:59").format(true), "11:59 PM");
+        assert_eq!(time("23:59").format(false), "23:59");
+    }
+
+    #[test]
+    fn parse_hh_mm() {
+        let parsed = TimeKey::try_from("07:45").unwrap();
+        assert_eq!((parsed.hour(), parsed.minute()), (7, 45));
+        assert_eq!(time("23:59").to_string(), "23:59");
+        assert_eq!(time("00:00"), TimeKey::new(0, 0).unwrap());
+
+        for bad in &[
+            "", "7:45", "07:4", " 07:45", "07:45 ", "24:00", "12:60", "12:00:00", "+1:30",
+        ] {
+            assert!(
+                matches!(TimeKey::try_from(*bad), Err(Error::InvalidTime(ref s)) if s == bad),
+                "{:?} parsed",
+                bad
+            );
+        }
+        assert!(TimeKey::new(24, 0).is_none());
+        assert!(TimeKey::new(0, 60).is_none());
+    }
+
+    #[test]
+    fn ordered_from_midnight_to_midnight() {
+        assert!(time("00:00") < time("00:01"));
+        assert!(time("09:59") < time("10:00"));
+        assert!(time("23:59") > time("00:00"));
+        let mut times = vec![time("12:00"), time("23:59"), time("00:00"), time("11:59")];
+        times.sort();
+        assert_eq!(
+            times,
+            [time("00:00"), time("11:59"), time("12:00"), time("23:59")]
+        );
+    }
+
+    #[test]
+    fn succ_and_pred_wrap_around_midnight() {
+        assert_eq!(time("23:59").succ(), time("00:00"));
+        assert_eq!(time("00:00").pred(), time("23:59"));
+        assert_eq!(time("12:59").succ(), time("13:00"));
+        assert_eq!(time("13:00").pred(), time("12:59"));
+        assert_eq!(time("12:30").succ().pred(), time("12:30"));
+
+        // a whole day of minutes ends back where it started
+        let start = time("17:23");
+        let mut t = start;
+        for _ in 0..24 * 60 {
+            t = t.succ();
+        }
+        assert_eq!(t, start);
+    }
+
+    #[test]
+    fn minutes_until_wraps_into_the_next_day() {
+        assert_eq!(time("12:00").minutes_until(time("12:00")), 0);
+        assert_eq!(time("12:00").minutes_until(time("12:01")), 1);
+        assert_eq!(time("09:15").minutes_until(time("17:45")), 8 * 60 + 30);
+        // an earlier time is tomorrow
+        assert_eq!(time("23:50").minutes_until(time("00:10")), 20);
+        assert_eq!(time("12:01").minutes_until(time("12:00")), 24 * 60 - 1);
+        assert_eq!(time("00:00").minutes_until(time("23:59")), 24 * 60 - 1);
+    }
+}
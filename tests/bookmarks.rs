@@ -0,0 +1,55 @@
+mod common;
+
+use literal::{Bookmarks, Quote};
+
+use common::*;
+
+#[test]
+fn bookmarked_quotes_load_back() {
+    let dir = TempDir::new();
+    let bookmarks = Bookmarks::new(dir.join("nested").join("bookmarks.csv"));
+    assert!(bookmarks.load().unwrap().is_empty());
+
+    let noon = Quote::new("12:00", "noon", "It was noon.", "Source", "Author").unwrap();
+    // the delimiter, quotes and line breaks all survive being written out
+    let awkward = Quote::new(
+        "23:59",
+        "late",
+        "\"Late,\" she said | and then,\nlater.",
+        "Source | Two",
+        "Author",
+    )
+    .unwrap();
+    assert!(bookmarks.add(&noon).unwrap());
+    assert!(bookmarks.add(&awkward).unwrap());
+    assert_eq!(bookmarks.load().unwrap(), [noon.clone(), awkward]);
+
+    // adding it again doesn't duplicate it
+    assert!(!bookmarks.add(&noon).unwrap());
+    assert_eq!(bookmarks.load().unwrap().len(), 2);
+}
+
+#[test]
+fn bookmark_then_list_the_bookmarks() {
+    let home = TempDir::new();
+    let bookmark = |at| {
+        let args = ["--no-builtin", "--file", QUOTES, "bookmark", "--at", at];
+        run_in(&home, &args)
+    };
+
+    assert_eq!(
+        stdout(&bookmark("12:10")),
+        "bookmarked Dan Poet – Late Again\n"
+    );
+    assert_eq!(
+        stdout(&bookmark("23:50")),
+        "bookmarked Ann Author – Party Book\n"
+    );
+    assert_eq!(stdout(&bookmark("12:10")), "already bookmarked\n");
+
+    let out = run_in(&home, &["bookmarks", "--attribution-only"]);
+    assert_eq!(
+        stdout(&out),
+        "12:10 — Dan Poet, Late Again\n23:50 — Ann Author, Party Book\n"
+    );
+}
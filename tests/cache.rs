@@ -0,0 +1,71 @@
+mod common;
+
+use std::fs::File;
+use std::time::{Duration, SystemTime};
+
+use literal::{FileFormat, Quote, QuoteCache};
+
+use common::*;
+
+fn quotes() -> Vec<Quote> {
+    vec![
+        Quote::new("12:00", "noon", "It was noon.", "Source", "Author").unwrap(),
+        Quote::new("23:59", "late", "It was late.", "Source", "Author").unwrap(),
+    ]
+}
+
+#[test]
+fn cached_quotes_load_back() {
+    let dir = TempDir::new();
+    let file = dir.join("quotes.csv");
+    std::fs::write(&file, "").unwrap();
+    let cache = QuoteCache::new(dir.join("cache"));
+
+    assert!(cache.load(&file, FileFormat::Csv).is_none());
+    cache.store(&file, FileFormat::Csv, &quotes(), 3).unwrap();
+    assert_eq!(cache.load(&file, FileFormat::Csv), Some((quotes(), 3)));
+}
+
+#[test]
+fn modifying_the_file_invalidates_it() {
+    let dir = TempDir::new();
+    let file = dir.join("quotes.csv");
+    std::fs::write(&file, "").unwrap();
+    let cache = QuoteCache::new(dir.join("cache"));
+    cache.store(&file, FileFormat::Csv, &quotes(), 0).unwrap();
+
+    let later = SystemTime::now() + Duration::from_secs(60);
+    File::options()
+        .write(true)
+        .open(&file)
+        .unwrap()
+        .set_modified(later)
+        .unwrap();
+    assert!(cache.load(&file, FileFormat::Csv).is_none());
+}
+
+#[test]
+fn parsing_as_another_format_is_a_miss() {
+    let dir = TempDir::new();
+    let file = dir.join("quotes.txt");
+    std::fs::write(&file, "").unwrap();
+    let cache = QuoteCache::new(dir.join("cache"));
+    cache.store(&file, FileFormat::Csv, &quotes(), 0).unwrap();
+
+    assert!(cache.load(&file, FileFormat::Json).is_none());
+    assert!(cache.load(&file, FileFormat::Csv).is_some());
+}
+
+#[test]
+fn a_corrupt_entry_is_a_miss() {
+    let dir = TempDir::new();
+    let file = dir.join("quotes.csv");
+    std::fs::write(&file, "").unwrap();
+    let cache = QuoteCache::new(dir.join("cache"));
+    cache.store(&file, FileFormat::Csv, &quotes(), 0).unwrap();
+
+    for entry in std::fs::read_dir(dir.join("cache")).unwrap() {
+        std::fs::write(entry.unwrap().path(), b"\xff\xff\xff\xff not bincode").unwrap();
+    }
+    assert!(cache.load(&file, FileFormat::Csv).is_none());
+}
@@ -0,0 +1,1010 @@
+mod common;
+
+use std::collections::HashSet;
+
+use literal::ColorSet;
+use termcolor::{Buffer, ColorSpec, WriteColor};
+
+use common::*;
+
+/// What's written to switch to the spec, to look for in the output
+fn escape(spec: &ColorSpec) -> String {
+    let mut buffer = Buffer::ansi();
+    buffer.set_color(spec).unwrap();
+    String::from_utf8(buffer.into_inner()).unwrap()
+}
+
+#[test]
+fn a_seed_picks_the_same_quote() {
+    let args = ["--at", "12:00", "--attribution-only", "--seed", "7"];
+    let first = stdout(&fixture(&args));
+    assert!(first.starts_with("12:00 — "));
+    assert_eq!(first, stdout(&fixture(&args)));
+
+    let picks = (0..20)
+        .map(|seed| {
+            let seed = seed.to_string();
+            stdout(&fixture(&[
+                "--at",
+                "12:00",
+                "--attribution-only",
+                "--seed",
+                &seed,
+            ]))
+        })
+        .collect::<HashSet<_>>();
+    assert!(picks.len() > 1);
+}
+
+#[test]
+fn no_wrap_prints_the_quote_on_one_line() {
+    let args = ["--at", "12:10", "--width", "20"];
+    let wrapped = stdout(&fixture(&args));
+    let unwrapped = stdout(&fixture(&[&args[..], &["--no-wrap"]].concat()));
+
+    let quote = "It was ten past twelve, and she was late.";
+    assert!(!wrapped.contains(quote));
+    assert!(unwrapped.lines().any(|line| line.trim() == quote));
+    assert!(unwrapped.lines().count() < wrapped.lines().count());
+}
+
+#[test]
+fn file_loads_quotes_along_with_the_built_in_ones() {
+    let total = |args: &[&str]| {
+        let out = run(&[args, &["count", "--total"]].concat());
+        stdout(&out).trim().parse::<usize>().unwrap()
+    };
+    let builtin = total(&[]);
+    assert_eq!(total(&["--no-builtin", "--file", QUOTES]), 6);
+    assert_eq!(total(&["--file", QUOTES]), builtin + 6);
+
+    let out = fixture(&["--at", "12:10", "--attribution-only"]);
+    assert_eq!(stdout(&out), "12:10 — Dan Poet, Late Again\n");
+}
+
+#[test]
+fn no_color_turns_the_colors_off() {
+    let home = TempDir::new();
+    let colored = |no_color: Option<&str>, args: &[&str]| {
+        let args = [&["--no-builtin", "--file", QUOTES, "--at", "12:10"], args].concat();
+        let mut command = command(&home, &args);
+        command.env("TERM", "xterm");
+        if let Some(value) = no_color {
+            command.env("NO_COLOR", value);
+        }
+        command.output().unwrap().stdout.contains(&b'\x1b')
+    };
+
+    assert!(colored(None, &[]));
+    assert!(!colored(Some("1"), &[]));
+    assert!(colored(Some("1"), &["--color", "always"]));
+}
+
+#[test]
+fn exact_fails_for_a_minute_without_quotes() {
+    let out = fixture(&["--at", "12:05", "--exact"]);
+    assert_eq!(out.status.code(), Some(4));
+    assert_eq!(stderr(&out), "no quote for 12:05\n");
+
+    let out = fixture(&["--at", "12:10", "--exact", "--attribution-only"]);
+    assert_eq!(stdout(&out), "12:10 — Dan Poet, Late Again\n");
+
+    let out = fixture(&["--at", "12:05", "--exact", "--direction", "forward"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn bold_and_italic_are_added_to_the_colors() {
+    let styled = |args: &[&str]| {
+        let args = [&["--at", "12:10", "--color", "always"], args].concat();
+        stdout(&fixture(&args))
+    };
+    let colors = ColorSet::standard();
+    let mut bold = colors.highlight.clone();
+    bold.set_bold(true);
+    let mut italic = colors.inactive.clone();
+    italic.set_italic(true);
+
+    let plain = styled(&[]);
+    assert!(plain.contains(&format!("{}t", escape(&colors.highlight))));
+    assert!(!plain.contains(&escape(&bold)));
+
+    let out = styled(&["--highlight-bold", "--inactive-italic"]);
+    assert!(out.contains(&format!("{}t", escape(&bold))));
+    assert!(out.contains(&format!("{}I", escape(&italic))));
+    assert!(!out.contains(&escape(&colors.highlight)));
+}
+
+#[test]
+fn search_highlights_the_query_but_keeps_the_context_in_json() {
+    let out = fixture(&["search", "square", "--format", "json"]);
+    let quotes = stdout(&out).lines().map(String::from).collect::<Vec<_>>();
+    assert_eq!(quotes.len(), 1);
+    assert!(quotes[0].contains(r#""context":"noon""#));
+
+    let out = fixture(&["search", "square", "--context-marker", "[]"]);
+    assert!(stdout(&out).contains("Noon, and the [square] was empty."));
+}
+
+#[test]
+fn validate_reports_each_broken_row() {
+    let out = run(&["validate", "--file", "tests/fixtures/broken.csv"]);
+    assert_eq!(out.status.code(), Some(3));
+    let report = stdout(&out);
+    let lines = report.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "tests/fixtures/broken.csv:");
+    assert_eq!(
+        lines[1],
+        "line 2: expected 5 columns, found 3, a field with a line break has to be in double quotes"
+    );
+    assert_eq!(lines[2], "line 3: expected 5 columns, found 6");
+    assert_eq!(lines[3], "line 4: time 'noon' is not HH:MM");
+    assert_eq!(lines[4], "line 5: time '24:00' is outside of 00:00..23:59");
+    assert_eq!(
+        lines[5],
+        "line 6: context 'three past noon' does not appear in the quote"
+    );
+    assert!(lines[6].starts_with("line 7: cannot be read: "));
+    assert_eq!(lines[7], "6 problems found");
+
+    let dir = TempDir::new();
+    let file = dir.join("one.csv");
+    std::fs::write(
+        &file,
+        "12:00|noon|At noon.|Source|Author\nnoon|noon|At noon.|Source|Author\n",
+    )
+    .unwrap();
+    let out = run_in(&dir, &["validate", "--file", file.to_str().unwrap()]);
+    assert_eq!(stdout(&out).lines().last(), Some("1 problem found"));
+
+    let out = run(&["validate", "--file", QUOTES]);
+    assert_eq!(out.status.code(), Some(0));
+    assert!(stdout(&out).ends_with("no problems found\n"));
+}
+
+#[test]
+fn files_are_merged_without_duplicates() {
+    let out = run(&[
+        "--no-builtin",
+        "--file",
+        QUOTES,
+        "--file",
+        MORE_QUOTES,
+        "count",
+        "--total",
+    ]);
+    assert_eq!(stdout(&out), "7\n");
+
+    let out = run(&["--no-builtin", "--file", MORE_QUOTES, "--at", "18:30"]);
+    assert!(stdout(&out).contains("half past six"));
+}
+
+#[test]
+fn files_without_any_quotes_are_an_error() {
+    let dir = TempDir::new();
+    let empty = dir.join("empty.csv");
+    std::fs::write(&empty, "").unwrap();
+    let broken = dir.join("broken.csv");
+    std::fs::write(&broken, "noon|noon|At noon.|Source|Author\n").unwrap();
+
+    for file in &[empty, broken] {
+        let out = run_in(&dir, &["--no-builtin", "--file", file.to_str().unwrap()]);
+        assert_eq!(out.status.code(), Some(3));
+        assert!(stderr(&out).starts_with("no usable quotes in "));
+    }
+}
+
+#[test]
+fn an_author_filter_wraps_around_midnight() {
+    let shown = |args: &[&str]| {
+        let out = fixture(&[&["--author", "ann", "--attribution-only"], args].concat());
+        stdout(&out)
+    };
+    assert_eq!(
+        shown(&["--at", "00:04", "--direction", "backward"]),
+        "23:50 — Ann Author, Party Book\n"
+    );
+    assert_eq!(
+        shown(&["--at", "23:51", "--direction", "forward"]),
+        "00:05 — Ann Author, The Bells\n"
+    );
+    // the other quotes at 12:00 don't count
+    assert_eq!(
+        shown(&["--at", "12:00", "--direction", "backward"]),
+        "00:05 — Ann Author, The Bells\n"
+    );
+
+    let out = fixture(&["--source", "square", "count", "--total"]);
+    assert_eq!(stdout(&out), "1\n");
+}
+
+#[test]
+fn a_filter_that_leaves_nothing_is_an_error() {
+    let out = fixture(&["--author", "nobody"]);
+    assert_eq!(out.status.code(), Some(4));
+    assert_eq!(
+        stderr(&out),
+        "no quotes are left after filtering by author and source\n"
+    );
+}
+
+#[test]
+fn excludes_are_applied_after_includes() {
+    let total = |args: &[&str]| {
+        let out = fixture(&[args, &["count", "--total"]].concat());
+        stdout(&out).trim().parse::<usize>().unwrap()
+    };
+    assert_eq!(total(&["--author", "ben"]), 2);
+    assert_eq!(total(&["--exclude-source", "fog"]), 5);
+    assert_eq!(total(&["--author", "ben", "--exclude-source", "fog"]), 1);
+    assert_eq!(total(&["--author", "author", "--exclude-author", "ben"]), 2);
+
+    let out = fixture(&[
+        "--author",
+        "ben",
+        "--exclude-source",
+        "fog",
+        "--at",
+        "12:00",
+        "--attribution-only",
+    ]);
+    assert_eq!(stdout(&out), "12:00 — Ben Writer, Square Book\n");
+
+    // an exclude wins over an include for the same quote
+    let out = fixture(&["--author", "ben", "--exclude-author", "writer"]);
+    assert_eq!(out.status.code(), Some(4));
+}
+
+#[test]
+fn index_picks_a_quote_for_the_minute() {
+    let shown = |index| {
+        let out = fixture(&["--at", "12:00", "--index", index, "--attribution-only"]);
+        (out.status.code(), stdout(&out))
+    };
+    assert_eq!(
+        shown("0"),
+        (Some(0), "12:00 — Ben Writer, Square Book\n".into())
+    );
+    assert_eq!(
+        shown("1"),
+        (Some(0), "12:00 — Cara Novelist, Train Story\n".into())
+    );
+    assert_eq!(
+        shown("2"),
+        (Some(0), "12:00 — Ben Writer, Fog Tales\n".into())
+    );
+
+    let out = fixture(&["--at", "12:00", "--index", "3"]);
+    assert_eq!(out.status.code(), Some(4));
+    assert_eq!(stderr(&out), "no such quote for 12:00, it has 3\n");
+}
+
+#[test]
+fn count_at_a_time_and_in_total() {
+    let count = |args: &[&str]| stdout(&fixture(&[&["count"], args].concat()));
+    assert_eq!(count(&["--at", "12:00"]), "3\n");
+    assert_eq!(count(&["--at", "00:05"]), "1\n");
+    assert_eq!(count(&["--at", "12:05"]), "0\n");
+    assert_eq!(count(&["--total"]), "6\n");
+}
+
+#[test]
+fn quote_only_leaves_out_the_attribution() {
+    let out = fixture(&["--at", "12:10", "--quote-only"]);
+    assert_eq!(
+        stdout(&out),
+        "  It was ten past twelve, and she was late.\n"
+    );
+
+    let out = fixture(&["--at", "12:10", "--quote-only", "--no-wrap"]);
+    let shown = stdout(&out);
+    assert!(!shown.contains("Dan Poet"));
+    assert!(!shown.contains("Late Again"));
+    assert_eq!(shown.lines().count(), 1);
+}
+
+#[test]
+fn attribution_only_leaves_out_the_quote() {
+    let out = fixture(&["--at", "23:50", "--attribution-only"]);
+    let shown = stdout(&out);
+    assert_eq!(shown, "23:50 — Ann Author, Party Book\n");
+    assert!(!shown.contains("party ended"));
+
+    // the time is always the quote's own
+    let out = fixture(&["--at", "23:55", "--attribution-only"]);
+    assert_eq!(stdout(&out), "23:50 — Ann Author, Party Book\n");
+}
+
+#[test]
+fn show_time_says_when_the_quote_is_for_another_minute() {
+    let header = |at| {
+        let out = fixture(&["--at", at, "--show-time", "--attribution-only"]);
+        stdout(&out).lines().next().unwrap().to_string()
+    };
+    assert_eq!(header("12:05"), "12:05 (showing 12:00)");
+    assert_eq!(header("00:00"), "00:00 (showing 23:50)");
+    assert_eq!(header("12:10"), "12:10");
+}
+
+#[test]
+fn file_format_reads_json_whatever_the_extension() {
+    let dir = TempDir::new();
+    let file = dir.join("quotes.txt");
+    let json = r#"[{"time": "09:30", "context": "half past nine",
+        "quote": "At half past nine it rained.", "source": "Rain", "author": "Fay"}]"#;
+    std::fs::write(&file, json).unwrap();
+    let file = file.to_str().unwrap();
+
+    let out = run_in(
+        &dir,
+        &[
+            "--no-builtin",
+            "--file",
+            file,
+            "--file-format",
+            "json",
+            "--attribution-only",
+        ],
+    );
+    assert_eq!(stdout(&out), "09:30 — Fay, Rain\n");
+
+    // read as CSV, it has no quotes
+    let out = run_in(&dir, &["--no-builtin", "--file", file]);
+    assert_eq!(out.status.code(), Some(3));
+
+    std::fs::rename(file, dir.join("quotes.json")).unwrap();
+    let file = dir.join("quotes.json");
+    let out = run_in(
+        &dir,
+        &[
+            "--no-builtin",
+            "--file",
+            file.to_str().unwrap(),
+            "--attribution-only",
+        ],
+    );
+    assert_eq!(stdout(&out), "09:30 — Fay, Rain\n");
+}
+
+#[test]
+fn bash_completions_mention_the_flags_and_subcommands() {
+    let out = run(&["completions", "bash"]);
+    assert!(out.status.success());
+    let script = stdout(&out);
+    for word in &["--at", "--color", "--file", "search", "range"] {
+        assert!(script.contains(word), "no {} in the completions", word);
+    }
+
+    for shell in &["zsh", "fish", "powershell", "elvish"] {
+        assert!(!run(&["completions", shell]).stdout.is_empty());
+    }
+    assert_eq!(run(&["completions", "tcsh"]).status.code(), Some(2));
+}
+
+#[test]
+fn man_page_documents_the_flags_and_subcommands() {
+    let out = run(&["man"]);
+    assert!(out.status.success());
+    let page = stdout(&out);
+    assert!(page.starts_with(".TH LITERAL 1 "));
+    assert!(page.contains("literal \\- displays a quote"));
+    // dashes are escaped so they aren't hyphens
+    assert!(page.contains(".B \\-\\-at"));
+    assert!(page.contains(".B \\-\\-no\\-wrap"));
+    assert!(page.contains(".B \\-\\-at <HH:MM>\nuse this time instead"));
+    // each subcommand's own flags are under it, and not again at the top
+    let commands = page.split(".SH COMMAND OPTIONS").nth(1).unwrap();
+    assert!(commands.contains(".SS search"));
+    assert!(commands.contains(".SS dump\\-csv"));
+    assert_eq!(page.matches(".B \\-\\-at ").count(), 1);
+    assert!(page.contains(".SH EXIT STATUS\n.TP\n.B 0\neverything went fine"));
+}
+
+#[test]
+fn typewriter_prints_at_once_when_not_a_terminal() {
+    let started = std::time::Instant::now();
+    // at one character a second this would take most of a minute on a terminal
+    let typed = fixture(&["--at", "12:10", "--typewriter", "1", "--color", "always"]);
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+
+    let plain = fixture(&["--at", "12:10", "--color", "always"]);
+    assert!(typed.status.success());
+    assert_eq!(stdout(&typed), stdout(&plain));
+    assert!(stdout(&typed).contains("Dan Poet"));
+}
+
+#[test]
+fn length_filters_are_inclusive() {
+    // the quotes are 49, 31, 33, 29, 41 and 35 characters long
+    let total = |args: &[&str]| {
+        let out = fixture(&[args, &["count", "--total"]].concat());
+        stdout(&out).trim().parse::<usize>().unwrap()
+    };
+    assert_eq!(total(&["--min-length", "41"]), 2);
+    assert_eq!(total(&["--max-length", "29"]), 1);
+    assert_eq!(total(&["--min-length", "31", "--max-length", "35"]), 3);
+
+    // noon's quotes are all too short, so an earlier minute is used
+    let out = fixture(&["--min-length", "40", "--at", "12:05", "--attribution-only"]);
+    assert_eq!(stdout(&out), "00:05 — Ann Author, The Bells\n");
+
+    let out = fixture(&["--min-length", "50"]);
+    assert_eq!(out.status.code(), Some(4));
+}
+
+#[test]
+fn log_has_a_line_for_each_quote_shown() {
+    let dir = TempDir::new();
+    let log = dir.join("shown.log");
+    let log = log.to_str().unwrap();
+    let args = |extra: &[&str]| {
+        let mut args = vec![
+            "--no-builtin",
+            "--file",
+            QUOTES,
+            "--now",
+            "2024-01-01 09:00",
+        ];
+        args.extend(extra);
+        args.extend(&["--log", log]);
+        run_in(&dir, &args)
+    };
+
+    args(&["--at", "12:10", "--at", "23:50"]);
+    assert_eq!(
+        std::fs::read_to_string(log).unwrap(),
+        concat!(
+            "2024-01-01T09:00:00 12:10 Dan Poet – Late Again\n",
+            "2024-01-01T09:00:00 23:50 Ann Author – Party Book\n",
+        )
+    );
+
+    // it's appended to, and can be JSON lines
+    args(&["--at", "00:05", "--format", "json"]);
+    let logged = std::fs::read_to_string(log).unwrap();
+    let last = logged.lines().last().unwrap();
+    let value: serde_json::Value = serde_json::from_str(last).unwrap();
+    assert_eq!(logged.lines().count(), 3);
+    assert_eq!(value["time"], "00:05");
+    assert_eq!(value["author"], "Ann Author");
+    assert_eq!(value["source"], "The Bells");
+    assert_eq!(value["shown"], "2024-01-01T09:00:00");
+}
+
+#[test]
+fn stats_summarize_the_quotes() {
+    let out = fixture(&["stats"]);
+    assert_eq!(
+        stdout(&out),
+        concat!(
+            "            quotes 6\n",
+            "           authors 4\n",
+            "           sources 6\n",
+            "   minutes covered 4 of 1440\n",
+            " minutes uncovered 1436\n",
+            // 218 characters between them, and the upper of the middle two
+            "    average length 36 characters\n",
+            "     median length 35 characters\n",
+            "    busiest minute 12:00 with 3 quotes\n",
+            // Ann Author and Ben Writer have two each
+            "        top author Ann Author with 2 quotes\n",
+            "        top source Fog Tales with 1 quote\n",
+        )
+    );
+
+    let out = fixture(&["--author", "ann", "stats"]);
+    let shown = stdout(&out);
+    assert!(shown.contains("   minutes covered 2 of 1440\n"));
+    assert!(shown.contains("    busiest minute 00:05 with 1 quote\n"));
+}
+
+#[test]
+fn context_marker_marks_only_the_context_without_colors() {
+    let out = fixture(&["--at", "12:10", "--context-marker", "«»", "--quote-only"]);
+    assert_eq!(
+        stdout(&out),
+        "  It was «ten past twelve», and she was late.\n"
+    );
+
+    let out = fixture(&["--at", "12:10", "--context-marker", "**", "--no-wrap"]);
+    let shown = stdout(&out);
+    assert!(shown.contains("It was *ten past twelve*, and she was late."));
+    assert_eq!(shown.matches('*').count(), 2);
+
+    // colors already show where it is
+    let out = fixture(&[
+        "--at",
+        "12:10",
+        "--context-marker",
+        "«»",
+        "--color",
+        "always",
+    ]);
+    assert!(!stdout(&out).contains('«'));
+}
+
+#[test]
+fn file_dash_reads_the_quotes_from_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let home = TempDir::new();
+    let args = [
+        "--no-builtin",
+        "--file",
+        "-",
+        "--at",
+        "07:00",
+        "--attribution-only",
+    ];
+    let mut child = command(&home, &args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let csv = concat!(
+        "07:00|seven|At seven the milk came.|Morning |Gus Grey \n",
+        "08:00|eight|At eight the post came.|Morning |Gus Grey \n",
+    );
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(csv.as_bytes())
+        .unwrap();
+    let out = child.wait_with_output().unwrap();
+
+    assert!(out.status.success());
+    assert_eq!(stdout(&out), "07:00 — Gus Grey, Morning\n");
+}
+
+#[test]
+fn output_writes_the_quote_to_a_file() {
+    let dir = TempDir::new();
+    let file = dir.join("quote.txt");
+    let path = file.to_str().unwrap();
+    let show = |args: &[&str]| {
+        let all = [&["--no-builtin", "--file", QUOTES, "--output", path], args].concat();
+        let out = run_in(&dir, &all);
+        assert!(out.stdout.is_empty());
+        std::fs::read_to_string(&file).unwrap()
+    };
+
+    let written = show(&["--at", "12:10"]);
+    assert_eq!(
+        written,
+        "\n  It was ten past twelve, and she was late.\n\n        Dan Poet – Late Again\n"
+    );
+
+    let written = show(&["--at", "23:50", "--attribution-only", "--append"]);
+    assert!(written.starts_with("\n  It was ten past twelve"));
+    assert!(written.ends_with("Late Again\n23:50 — Ann Author, Party Book\n"));
+
+    // it's replaced without --append, and colors have to be asked for
+    let written = show(&["--at", "23:50", "--attribution-only", "--color", "always"]);
+    assert!(written.starts_with('\x1b'));
+    assert!(written.contains("23:50 — Ann Author, Party Book"));
+    assert!(!written.contains("Late Again"));
+}
+
+#[test]
+fn dry_run_reports_the_match_without_the_quote() {
+    let out = fixture(&["--at", "12:10", "--dry-run"]);
+    assert_eq!(
+        stdout(&out),
+        "12:10 -> 12:10 Dan Poet – Late Again (1 candidate)\n"
+    );
+
+    let out = fixture(&["--at", "12:05", "--dry-run", "--author", "ben", "--12h"]);
+    let line = stdout(&out);
+    assert!(line.starts_with("12:05 PM -> 12:00 PM Ben Writer – "));
+    assert!(line.ends_with(" (2 candidates)\n"));
+    assert!(!line.contains("square was empty") && !line.contains("fog had lifted"));
+
+    // the clock doesn't wait for the next minute
+    let out = fixture(&["--now", "2024-01-01 23:55", "--dry-run", "clock"]);
+    assert!(out.status.success());
+    assert_eq!(
+        stdout(&out),
+        "23:55 -> 23:50 Ann Author – Party Book (1 candidate)\n"
+    );
+}
+
+#[test]
+fn verbose_reports_on_stderr() {
+    let args = [
+        "--no-builtin",
+        "--file",
+        "tests/fixtures/broken.csv",
+        "--at",
+        "12:00",
+    ];
+    let quiet = run(&args);
+    let out = run(&[&args[..], &["-v"]].concat());
+    // stdout is the same, so it can still be piped
+    assert_eq!(stdout(&out), stdout(&quiet));
+    assert!(stderr(&quiet).is_empty());
+
+    let lines = stderr(&out);
+    let lines = lines.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "loaded 2 quotes, skipped 5 malformed rows");
+    assert!(lines[1].starts_with("width is 60, color is "));
+    assert_eq!(lines.len(), 2);
+
+    // and how far it had to look for a quote with more
+    let out = fixture(&["--at", "12:05", "-vv"]);
+    assert!(stderr(&out).ends_with("12:05 matched 12:00, 5 minutes away\n"));
+}
+
+#[test]
+fn now_pretends_it_is_that_time() {
+    let shown = |now| {
+        let out = fixture(&["--now", now, "--attribution-only"]);
+        stdout(&out)
+    };
+    assert_eq!(shown("2024-01-01 00:05"), "00:05 — Ann Author, The Bells\n");
+    assert_eq!(
+        shown("1999-12-31 23:59"),
+        "23:50 — Ann Author, Party Book\n"
+    );
+
+    // the date decides the daily quote
+    let daily = |now: &str| {
+        let out = fixture(&["--now", now, "--daily", "--attribution-only"]);
+        stdout(&out)
+    };
+    assert_eq!(daily("2024-03-01 12:00"), daily("2024-03-01 12:00"));
+    let days = (1..=28).map(|day| daily(&format!("2024-02-{:02} 12:00", day)));
+    assert!(days.collect::<HashSet<_>>().len() > 1);
+
+    assert_eq!(
+        fixture(&["--now", "2024-01-01 25:00"]).status.code(),
+        Some(2)
+    );
+}
+
+#[test]
+fn fuzzy_highlights_a_context_with_a_typo() {
+    let dir = TempDir::new();
+    let file = dir.join("typo.csv");
+    std::fs::write(
+        &file,
+        "09:30|half pst nine|At half past nine it rained.|Rain |Fay \n",
+    )
+    .unwrap();
+    let shown = |fuzzy| {
+        let args = [
+            "--no-builtin",
+            "--file",
+            file.to_str().unwrap(),
+            "--context-marker",
+            "[]",
+            "--quote-only",
+            "--fuzzy",
+            fuzzy,
+        ];
+        stdout(&run_in(&dir, &args))
+    };
+    assert_eq!(shown("2"), "  At [half past nine] it rained.\n");
+    assert_eq!(shown("0"), "  At half past nine it rained.\n");
+}
+
+#[test]
+fn a_color_flag_overrides_the_theme() {
+    let shown = |args: &[&str]| {
+        let all = [&["--at", "12:10", "--color", "always"], args].concat();
+        stdout(&fixture(&all))
+    };
+    let solarized = ColorSet::solarized();
+
+    let out = shown(&["--theme", "solarized"]);
+    assert!(out.contains(&format!("{}t", escape(&solarized.highlight))));
+    assert!(out.contains(&format!("{}I", escape(&solarized.inactive))));
+
+    let out = shown(&["--theme", "solarized", "--highlight", "green"]);
+    let mut highlight = solarized.highlight.clone();
+    highlight.set_fg(Some(termcolor::Color::Green));
+    assert!(out.contains(&format!("{}t", escape(&highlight))));
+    assert!(!out.contains(&escape(&solarized.highlight)));
+    // the rest of the theme is left alone
+    assert!(out.contains(&format!("{}I", escape(&solarized.inactive))));
+}
+
+#[test]
+fn the_config_is_used_unless_a_flag_is_given() {
+    let home = TempDir::new();
+    let config = home.join("literal.toml");
+    let toml = format!("direction = \"forward\"\nfile = \"{}\"\n", QUOTES);
+    std::fs::write(&config, toml).unwrap();
+    let config = config.to_str().unwrap();
+    let dry_run = |args: &[&str]| {
+        let all = [
+            &[
+                "--no-builtin",
+                "--config",
+                config,
+                "--at",
+                "12:05",
+                "--dry-run",
+            ],
+            args,
+        ]
+        .concat();
+        stdout(&run_in(&home, &all))
+    };
+
+    // the file comes from the config too
+    assert!(dry_run(&[]).starts_with("12:05 -> 12:10 Dan Poet"));
+    assert!(dry_run(&["--direction", "backward"]).starts_with("12:05 -> 12:00 "));
+
+    // the user's own config is read without --config
+    let user = home.join(".config").join("literal");
+    std::fs::create_dir_all(&user).unwrap();
+    std::fs::write(user.join("config.toml"), "direction = \"forward\"\n").unwrap();
+    let out = run_in(
+        &home,
+        &[
+            "--no-builtin",
+            "--file",
+            QUOTES,
+            "--at",
+            "12:05",
+            "--dry-run",
+        ],
+    );
+    assert!(stdout(&out).starts_with("12:05 -> 12:10 "));
+
+    std::fs::write(config, "direction = \"sideways\"\n").unwrap();
+    assert_eq!(run_in(&home, &["--config", config]).status.code(), Some(2));
+}
+
+#[test]
+fn pango_is_one_line_in_the_chosen_colors() {
+    let out = fixture(&[
+        "--at",
+        "12:10",
+        "--format",
+        "pango",
+        "--highlight",
+        "#123456",
+    ]);
+    let shown = stdout(&out);
+    assert_eq!(shown.lines().count(), 1);
+    assert!(shown.contains("<span foreground='#123456'>ten past twelve</span>"));
+    assert!(!shown.contains('\x1b'));
+}
+
+#[test]
+fn censored_quotes_are_left_out_or_masked() {
+    let home = TempDir::new();
+    let words = home.join("words.txt");
+    std::fs::write(&words, "late\n").unwrap();
+    let censor = format!("--censor={}", words.to_str().unwrap());
+
+    // the only quote for 12:10 has the word, so an earlier one is used
+    let out = fixture(&[&censor, "--at", "12:10", "--attribution-only"]);
+    assert!(stdout(&out).starts_with("12:00 — "));
+
+    let out = fixture(&[&censor, "--mask", "--at", "12:10", "--quote-only"]);
+    assert_eq!(
+        stdout(&out),
+        "  It was ten past twelve, and she was ****.\n"
+    );
+
+    let out = fixture(&["--mask", "--at", "12:10"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn each_author_gets_their_own_color() {
+    let colors = ColorSet::standard();
+    let tinted = |color| {
+        let mut spec = colors.active.clone();
+        spec.set_fg(Some(color));
+        escape(&spec)
+    };
+    let attribution = |at| {
+        let args = [
+            "--at",
+            at,
+            "--attribution-only",
+            "--color",
+            "always",
+            "--author-colors",
+            "Ann Author=blue;dan poet=green",
+        ];
+        stdout(&fixture(&args))
+    };
+
+    let ann = attribution("23:50");
+    assert!(ann.starts_with(&format!(
+        "{}23:50 — Ann Author",
+        tinted(termcolor::Color::Blue)
+    )));
+    let dan = attribution("12:10");
+    assert!(dan.starts_with(&format!(
+        "{}12:10 — Dan Poet",
+        tinted(termcolor::Color::Green)
+    )));
+    // anyone else has the usual color
+    let cara = stdout(&fixture(&[
+        "--at",
+        "12:00",
+        "--index",
+        "1",
+        "--attribution-only",
+        "--color",
+        "always",
+        "--author-colors",
+        "Ann Author=blue",
+    ]));
+    assert!(cara.starts_with(&format!("{}12:00 — Cara Novelist", escape(&colors.active))));
+}
+
+#[test]
+fn dump_csv_is_the_built_in_quotes_as_they_are() {
+    let out = run(&["dump-csv"]);
+    assert!(out.status.success());
+    assert!(out.stdout == literal::ANNOTATED_CSV);
+
+    let dir = TempDir::new();
+    let file = dir.join("quotes.csv");
+    let out = run_in(&dir, &["dump-csv", "--output", file.to_str().unwrap()]);
+    assert!(out.stdout.is_empty());
+    assert!(std::fs::read(&file).unwrap() == literal::ANNOTATED_CSV);
+}
+
+#[test]
+fn ascii_leaves_nothing_but_ascii() {
+    let modes: &[&[&str]] = &[
+        &[],
+        &["--no-wrap"],
+        &["--border", "rounded"],
+        &["--attribution-only"],
+        &["--format", "pango"],
+        &["--dry-run"],
+        &["--color", "always"],
+        &["list", "times"],
+    ];
+    for mode in modes {
+        let mut args = vec!["--at", "08:17", "--seed", "1"];
+        args.extend_from_slice(mode);
+        let plain = run(&args);
+        assert!(plain.status.success(), "{:?}", mode);
+        assert!(!plain.stdout.is_ascii(), "{:?}", mode);
+
+        args.push("--ascii");
+        let out = run(&args);
+        assert!(out.status.success(), "{:?}", mode);
+        assert!(!out.stdout.is_empty(), "{:?}", mode);
+        assert!(out.stdout.is_ascii(), "{:?}: {}", mode, stdout(&out));
+    }
+}
+
+#[test]
+fn list_times_shows_the_first_line_for_each_minute() {
+    let out = fixture(&["--width", "30", "list", "times"]);
+    assert!(out.status.success());
+    assert_eq!(
+        stdout(&out).lines().collect::<Vec<_>>(),
+        [
+            "00:05  It was five past… – Ann Author",
+            "12:00  Noon, and the square… – Ben Writer",
+            "12:10  It was ten past… – Dan Poet",
+            "23:50  At ten to midnight the… – Ann Author",
+        ]
+    );
+}
+
+#[test]
+fn duplicates_lists_each_quote_with_its_times() {
+    let out = run(&[
+        "--no-builtin",
+        "--file",
+        "tests/fixtures/duplicates.csv",
+        "duplicates",
+    ]);
+    assert!(out.status.success());
+    assert_eq!(
+        stdout(&out).lines().collect::<Vec<_>>(),
+        [
+            "07:15, 19:15",
+            "  At a quarter past seven the post came. – Fay Writer",
+            "quotes under more than one time: 1",
+        ]
+    );
+}
+
+#[test]
+fn lint_lists_the_quotes_that_cannot_be_highlighted() {
+    let lint = |strict: &[&str]| {
+        let mut args = vec!["--no-builtin", "--file", "tests/fixtures/unlit.csv", "lint"];
+        args.extend_from_slice(strict);
+        run(&args)
+    };
+    let expected = [
+        "06:10 Ida Poet: context 'ten past six' does not appear in the quote",
+        "07:00 Jo Writer: context 'seven o'clock' does not appear in the quote",
+        "2 of 4 quotes cannot be highlighted",
+    ];
+
+    let out = lint(&[]);
+    assert!(out.status.success());
+    assert_eq!(stdout(&out).lines().collect::<Vec<_>>(), expected);
+
+    let out = lint(&["--strict"]);
+    assert_eq!(out.status.code(), Some(3));
+    assert_eq!(stdout(&out).lines().collect::<Vec<_>>(), expected);
+
+    // with nothing to report it's fine either way
+    let out = fixture(&["lint", "--strict"]);
+    assert!(out.status.success());
+    assert_eq!(stdout(&out), "0 of 6 quotes cannot be highlighted\n");
+}
+
+#[test]
+fn each_at_prints_its_quote_in_order() {
+    let out = fixture(&["--at", "23:50", "--at", "00:05", "--at", "12:10"]);
+    assert!(out.status.success());
+    let out = stdout(&out);
+    let bylines = out
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.contains(" – "))
+        .collect::<Vec<_>>();
+    // in the order they were given rather than the order of the day
+    assert_eq!(
+        bylines,
+        [
+            "Ann Author – Party Book",
+            "Ann Author – The Bells",
+            "Dan Poet – Late Again",
+        ]
+    );
+    // each block starts with a blank line before its quote
+    assert!(out.starts_with("\n  At ten to midnight"));
+    assert!(out.contains("Party Book\n\n  It was five past midnight"));
+    assert!(out.contains("The Bells\n\n  It was ten past twelve"));
+}
+
+#[test]
+fn collate_sorts_accented_names_beside_the_rest() {
+    let accents = |args: &[&str]| {
+        let mut all = vec!["--no-builtin", "--file", "tests/fixtures/accents.csv"];
+        all.extend_from_slice(args);
+        let out = run(&all);
+        assert!(out.status.success());
+        stdout(&out)
+            .lines()
+            .map(|line| line.trim().to_string())
+            .collect::<Vec<_>>()
+    };
+    let names = ["list", "authors"];
+    assert_eq!(
+        accents(&names),
+        ["1 Eliot", "1 Zola", "1 zweig", "1 Éluard"]
+    );
+    assert_eq!(
+        accents(&[&names[..], &["--collate", "fr_FR.UTF-8"]].concat()),
+        ["1 Eliot", "1 Éluard", "1 Zola", "1 zweig"]
+    );
+    assert_eq!(
+        accents(&[&names[..], &["--collate", "C"]].concat()),
+        accents(&names)
+    );
+
+    // without Eliot, the top author is the first of the rest by name
+    let stats = ["--exclude-author", "eliot", "stats"];
+    let top = |lines: Vec<String>| lines.into_iter().find(|l| l.starts_with("top author"));
+    assert_eq!(
+        top(accents(&stats)).unwrap(),
+        "top author Zola with 1 quote"
+    );
+    assert_eq!(
+        top(accents(&[&stats[..], &["--collate", "fr"]].concat())).unwrap(),
+        "top author Éluard with 1 quote"
+    );
+
+    let out = run(&["list", "authors", "--collate", "en US"]);
+    assert_eq!(out.status.code(), Some(2));
+}
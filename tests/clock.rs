@@ -0,0 +1,112 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn every_takes_turns_showing_the_minutes_quotes() {
+    let home = TempDir::new();
+    let args = ["--at", "12:00", "--every", "1", "--attribution-only"];
+    let mut child = spawn_fixture(&home, &args);
+    let shown = read_lines(&mut child, 4);
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    // each of them once, in the order they were loaded, and then around again
+    assert_eq!(
+        shown,
+        [
+            "12:00 — Ben Writer, Square Book",
+            "12:00 — Cara Novelist, Train Story",
+            "12:00 — Ben Writer, Fog Tales",
+            "12:00 — Ben Writer, Square Book",
+        ]
+    );
+}
+
+#[test]
+fn log_skips_a_quote_that_is_still_shown() {
+    let home = TempDir::new();
+    let log = home.join("shown.log");
+    let args = [
+        "--now",
+        "2024-01-01 12:10",
+        "--log",
+        log.to_str().unwrap(),
+        "clock",
+        "--interval",
+        "1",
+    ];
+    let mut child = spawn_fixture(&home, &args);
+    read_lines(&mut child, 4);
+    // it wakes every second, but it's the same minute so the quote is kept
+    std::thread::sleep(std::time::Duration::from_millis(2500));
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&log).unwrap(),
+        "2024-01-01T12:10:00 12:10 Dan Poet – Late Again\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn ctrl_c_stops_the_clock_and_resets_the_colors() {
+    use std::io::Read;
+
+    let home = TempDir::new();
+    let args = [
+        "--now",
+        "2024-01-01 12:10",
+        "--color",
+        "always",
+        "clock",
+        "--clear",
+    ];
+    let mut child = spawn_fixture(&home, &args);
+    // the first quote is shown, then it's waiting for the next minute
+    read_lines(&mut child, 2);
+    let interrupted = std::process::Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(interrupted.success());
+
+    let status = child.wait().unwrap();
+    let mut rest = String::new();
+    child
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut rest)
+        .unwrap();
+    assert_eq!(status.code(), Some(0));
+    assert!(rest.ends_with("\x1b[0m\x1b[?25h"), "{:?}", rest);
+}
+
+#[test]
+fn clock_keeps_going_and_once_stops() {
+    let home = TempDir::new();
+    let mut child = spawn_fixture(&home, &["--now", "2024-01-01 12:10", "clock"]);
+    let shown = read_lines(&mut child, 4);
+    assert_eq!(shown[1], "  It was ten past twelve, and she was late.");
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let running = child.try_wait().unwrap().is_none();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert!(running);
+
+    let out = fixture(&["--now", "2024-01-01 12:10", "once", "--attribution-only"]);
+    assert!(out.status.success());
+    assert_eq!(stdout(&out), "12:10 — Dan Poet, Late Again\n");
+}
+
+#[test]
+fn clock_stops_once_it_gets_to_until() {
+    let args = ["--now", "2024-01-01 12:10", "clock", "--until", "12:10"];
+    let out = fixture(&args);
+    assert!(out.status.success());
+    let shown = stdout(&out);
+    assert!(shown.contains("Dan Poet – Late Again"));
+    assert!(shown.ends_with("0 minutes remaining\n"), "{:?}", shown);
+}
@@ -0,0 +1,102 @@
+// each test binary only uses some of these
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A few quotes, three of them at 12:00 and none at all for most minutes
+pub const QUOTES: &str = "tests/fixtures/quotes.csv";
+
+/// Two quotes, one of them the same as one in `QUOTES`
+pub const MORE_QUOTES: &str = "tests/fixtures/more.csv";
+
+/// A directory that's removed when it's dropped
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "literal-test-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::SeqCst)
+        );
+        let dir = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        Self(dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// The binary, run from the crate with the home as its home, so the user's
+/// own config, cache and bookmarks are left alone. Colors are only used when
+/// they are asked for
+pub fn command(home: &TempDir, args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_literal"));
+    command
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("HOME", home.path())
+        .env("TERM", "dumb")
+        .env_remove("NO_COLOR")
+        .env_remove("XDG_CACHE_HOME")
+        .env_remove("XDG_CONFIG_HOME")
+        .env_remove("XDG_DATA_HOME");
+    command
+}
+
+pub fn run_in(home: &TempDir, args: &[&str]) -> Output {
+    command(home, args).output().expect("the binary runs")
+}
+
+pub fn run(args: &[&str]) -> Output {
+    run_in(&TempDir::new(), args)
+}
+
+/// Runs with only the quotes from `QUOTES`
+pub fn fixture(args: &[&str]) -> Output {
+    let mut all = vec!["--no-builtin", "--file", QUOTES];
+    all.extend(args);
+    run(&all)
+}
+
+/// Starts the binary with only the quotes from `QUOTES`, for the ones that
+/// keep running until they're killed
+pub fn spawn_fixture(home: &TempDir, args: &[&str]) -> Child {
+    let mut all = vec!["--no-builtin", "--file", QUOTES];
+    all.extend(args);
+    command(home, &all)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("the binary starts")
+}
+
+/// Reads the next lines the child writes
+pub fn read_lines(child: &mut Child, count: usize) -> Vec<String> {
+    let stdout = BufReader::new(child.stdout.as_mut().unwrap());
+    stdout.lines().take(count).map(Result::unwrap).collect()
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
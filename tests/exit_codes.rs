@@ -0,0 +1,85 @@
+mod common;
+
+use common::*;
+
+#[test]
+fn success_is_0() {
+    assert_eq!(fixture(&["--at", "12:00"]).status.code(), Some(0));
+    assert_eq!(run(&["--help"]).status.code(), Some(0));
+    assert_eq!(run(&["--version"]).status.code(), Some(0));
+}
+
+#[test]
+fn bad_flags_or_config_are_2() {
+    assert_eq!(run(&["--no-such-flag"]).status.code(), Some(2));
+    assert_eq!(run(&["--at", "25:00"]).status.code(), Some(2));
+    assert_eq!(run(&["--width", "wide"]).status.code(), Some(2));
+
+    let home = TempDir::new();
+    let config = home.join("config.toml");
+    std::fs::write(&config, "width = \"wide\"\n").unwrap();
+    let out = run_in(&home, &["--config", config.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(2));
+
+    // a value in the config is checked like the flag's would be
+    std::fs::write(&config, "theme = \"neon\"\n").unwrap();
+    let out = run_in(&home, &["--config", config.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(2));
+    assert!(stderr(&out).starts_with("invalid theme in the config: "));
+
+    let out = fixture(&["clock", "--group-by-author"]);
+    assert_eq!(out.status.code(), Some(2));
+    assert_eq!(
+        stderr(&out),
+        "--group-by-author needs an --author to read through\n"
+    );
+    assert_eq!(run(&["--no-builtin"]).status.code(), Some(2));
+}
+
+#[test]
+fn quotes_that_cant_be_loaded_are_3() {
+    let home = TempDir::new();
+    let json = home.join("quotes.json");
+    std::fs::write(&json, "[{\"time\": ").unwrap();
+    let out = run_in(&home, &["--no-builtin", "--file", json.to_str().unwrap()]);
+    assert_eq!(out.status.code(), Some(3));
+
+    let out = run(&["validate", "--file", "tests/fixtures/broken.csv"]);
+    assert_eq!(out.status.code(), Some(3));
+    assert_eq!(stderr(&out), "the quotes have 6 problems\n");
+
+    let out = run(&[
+        "--no-builtin",
+        "--file",
+        "tests/fixtures/unlit.csv",
+        "lint",
+        "--strict",
+    ]);
+    assert_eq!(out.status.code(), Some(3));
+}
+
+#[test]
+fn no_quote_found_is_4() {
+    assert_eq!(
+        fixture(&["--at", "12:05", "--exact"]).status.code(),
+        Some(4)
+    );
+    assert_eq!(fixture(&["--author", "nobody"]).status.code(), Some(4));
+}
+
+#[test]
+fn reading_or_writing_failing_is_5() {
+    let out = run(&["--file", "tests/fixtures/missing.csv"]);
+    assert_eq!(out.status.code(), Some(5));
+    let out = fixture(&["--output", "tests/fixtures/missing/quote.txt"]);
+    assert_eq!(out.status.code(), Some(5));
+
+    let out = fixture(&["--censor=tests/fixtures/missing.txt"]);
+    assert_eq!(out.status.code(), Some(5));
+    assert!(
+        stderr(&out).starts_with("cannot read the words to censor 'tests/fixtures/missing.txt': ")
+    );
+    let out = fixture(&["--at", "12:00", "--log", "tests/fixtures/missing/log.txt"]);
+    assert_eq!(out.status.code(), Some(5));
+    assert!(stderr(&out).starts_with("cannot open the log "));
+}